@@ -0,0 +1,42 @@
+//! Runtime API of the rps pallet, implement it in the runtime with the query functions of
+//! the pallet:
+//!
+//! ```ignore
+//! impl pallet_rps_rpc_runtime_api::RpsApi<Block, AccountId, Hash, BlockNumber, Balance, MaxPlayers> for Runtime {
+//!     fn game_of(account: AccountId) -> Option<Hash> {
+//!         RockPaperScissor::game_of(account)
+//!     }
+//!     // --snip--
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_runtime::traits::Get;
+use sp_std::{fmt::Debug, vec::Vec};
+
+pub use pallet_rps::{Game, GameResult, MatchState};
+
+sp_api::decl_runtime_apis! {
+	/// Queries the games of the rps pallet.
+	pub trait RpsApi<AccountId, Hash, BlockNumber, Balance, MaxPlayers> where
+		AccountId: Codec + Clone + PartialEq + Debug,
+		Hash: Codec + Clone + PartialEq + Debug,
+		BlockNumber: Codec + Clone + PartialEq + Debug,
+		Balance: Codec + Clone + PartialEq + Debug,
+		MaxPlayers: Get<u32>,
+	{
+		/// Game the account is playing, if any.
+		fn game_of(account: AccountId) -> Option<Hash>;
+
+		/// Game with the id, if it exists.
+		fn game(game_id: Hash) -> Option<Game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>>;
+
+		/// Players who still have to act in the current phase of the game.
+		fn pending_players(game_id: Hash) -> Vec<AccountId>;
+
+		/// Block at which the current phase of the game times out.
+		fn phase_deadline(game_id: Hash) -> Option<BlockNumber>;
+	}
+}
@@ -0,0 +1,102 @@
+//! RPC interface of the rps pallet, exposing the queries of the `RpsApi` runtime API.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use std::fmt::Debug;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Get}};
+
+pub use pallet_rps_rpc_runtime_api::RpsApi as RpsRuntimeApi;
+use pallet_rps_rpc_runtime_api::Game;
+
+#[cfg(test)]
+mod tests;
+
+/// Queries the games of the rps pallet at the block `at`, or the best block if `at` is `None`.
+/// `Game` is the game type of the runtime, its player bound doesn't need to be serializable.
+#[rpc]
+pub trait RpsApi<BlockHash, AccountId, Hash, BlockNumber, Game> {
+	/// Game the account is playing, if any.
+	#[rpc(name = "rps_gameOf")]
+	fn game_of(&self, account: AccountId, at: Option<BlockHash>) -> Result<Option<Hash>>;
+
+	/// Game with the id, if it exists.
+	#[rpc(name = "rps_game")]
+	fn game(&self, game_id: Hash, at: Option<BlockHash>) -> Result<Option<Game>>;
+
+	/// Players who still have to act in the current phase of the game.
+	#[rpc(name = "rps_pendingPlayers")]
+	fn pending_players(&self, game_id: Hash, at: Option<BlockHash>) -> Result<Vec<AccountId>>;
+
+	/// Block at which the current phase of the game times out.
+	#[rpc(name = "rps_phaseDeadline")]
+	fn phase_deadline(&self, game_id: Hash, at: Option<BlockHash>) -> Result<Option<BlockNumber>>;
+}
+
+/// Implementation of the rps RPC methods on a client with the `RpsApi` runtime API.
+pub struct Rps<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Rps<C, Block> {
+	/// Creates the RPC handler with a client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error code of failed runtime API calls.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Converts a runtime API error into an RPC error.
+fn runtime_error(err: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Unable to query the rps runtime api.".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C, Block, AccountId, Hash, BlockNumber, Balance, MaxPlayers>
+	RpsApi<<Block as BlockT>::Hash, AccountId, Hash, BlockNumber, Game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>>
+	for Rps<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: RpsRuntimeApi<Block, AccountId, Hash, BlockNumber, Balance, MaxPlayers>,
+	AccountId: Codec + Clone + PartialEq + Debug,
+	Hash: Codec + Clone + PartialEq + Debug,
+	BlockNumber: Codec + Clone + PartialEq + Debug,
+	Balance: Codec + Clone + PartialEq + Debug,
+	MaxPlayers: Get<u32>,
+{
+	fn game_of(&self, account: AccountId, at: Option<<Block as BlockT>::Hash>) -> Result<Option<Hash>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().game_of(&at, account).map_err(runtime_error)
+	}
+
+	fn game(
+		&self,
+		game_id: Hash,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Option<Game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().game(&at, game_id).map_err(runtime_error)
+	}
+
+	fn pending_players(&self, game_id: Hash, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<AccountId>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().pending_players(&at, game_id).map_err(runtime_error)
+	}
+
+	fn phase_deadline(&self, game_id: Hash, at: Option<<Block as BlockT>::Hash>) -> Result<Option<BlockNumber>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().phase_deadline(&at, game_id).map_err(runtime_error)
+	}
+}
@@ -0,0 +1,148 @@
+use super::*;
+
+use std::collections::HashMap;
+
+use sp_api::{ApiError, ApiRef, NativeOrEncoded};
+use sp_blockchain::{BlockStatus, Info};
+use sp_core::H256;
+use sp_runtime::traits::NumberFor;
+use substrate_test_runtime_client::{Backend, Client, runtime::Block};
+
+struct MaxPlayers;
+impl Get<u32> for MaxPlayers {
+	fn get() -> u32 {
+		4
+	}
+}
+
+type TestGame = Game<H256, u64, u64, u64, MaxPlayers>;
+
+/// Game the runtime api fails to query, like a game stored with an old layout.
+fn broken_game() -> H256 {
+	H256::repeat_byte(9)
+}
+
+/// Client answering the runtime api from fixed games, headers come from a test client.
+#[derive(Clone)]
+struct TestApi {
+	client: Arc<Client<Backend>>,
+	player_game: HashMap<u64, H256>,
+	games: HashMap<H256, TestGame>,
+	pending: HashMap<H256, Vec<u64>>,
+	deadlines: HashMap<H256, u64>,
+}
+
+impl TestApi {
+	/// Player 1 and 2 playing `game_id`, player 2 still has to act until block 11.
+	fn new(game_id: H256) -> Self {
+		Self {
+			client: Arc::new(substrate_test_runtime_client::new()),
+			player_game: vec![(1, game_id), (2, game_id)].into_iter().collect(),
+			games: vec![(game_id, TestGame::default())].into_iter().collect(),
+			pending: vec![(game_id, vec![2])].into_iter().collect(),
+			deadlines: vec![(game_id, 11)].into_iter().collect(),
+		}
+	}
+}
+
+struct RuntimeApi {
+	inner: TestApi,
+}
+
+impl ProvideRuntimeApi<Block> for TestApi {
+	type Api = RuntimeApi;
+
+	fn runtime_api<'a>(&'a self) -> ApiRef<'a, Self::Api> {
+		RuntimeApi { inner: self.clone() }.into()
+	}
+}
+
+impl HeaderBackend<Block> for TestApi {
+	fn header(&self, id: BlockId<Block>) -> sp_blockchain::Result<Option<<Block as BlockT>::Header>> {
+		self.client.header(id)
+	}
+
+	fn info(&self) -> Info<Block> {
+		self.client.info()
+	}
+
+	fn status(&self, id: BlockId<Block>) -> sp_blockchain::Result<BlockStatus> {
+		self.client.status(id)
+	}
+
+	fn number(&self, hash: <Block as BlockT>::Hash) -> sp_blockchain::Result<Option<NumberFor<Block>>> {
+		self.client.number(hash)
+	}
+
+	fn hash(&self, number: NumberFor<Block>) -> sp_blockchain::Result<Option<<Block as BlockT>::Hash>> {
+		self.client.hash(number)
+	}
+}
+
+sp_api::mock_impl_runtime_apis! {
+	impl RpsRuntimeApi<Block, u64, H256, u64, u64, MaxPlayers> for RuntimeApi {
+		fn game_of(&self, account: u64) -> Option<H256> {
+			self.inner.player_game.get(&account).cloned()
+		}
+
+		#[advanced]
+		fn game(&self, _at: &BlockId<Block>, game_id: H256) -> std::result::Result<NativeOrEncoded<Option<TestGame>>, ApiError> {
+			if game_id == broken_game() {
+				return Err(ApiError::Application("game can't be decoded".into()));
+			}
+			Ok(self.inner.games.get(&game_id).cloned().into())
+		}
+
+		fn pending_players(&self, game_id: H256) -> Vec<u64> {
+			self.inner.pending.get(&game_id).cloned().unwrap_or_default()
+		}
+
+		fn phase_deadline(&self, game_id: H256) -> Option<u64> {
+			self.inner.deadlines.get(&game_id).cloned()
+		}
+	}
+}
+
+#[test]
+fn game_of_returns_game_of_player() {
+	let game_id = H256::repeat_byte(1);
+	let rps = Rps::new(Arc::new(TestApi::new(game_id)));
+
+	assert_eq!(rps.game_of(1, None), Ok(Some(game_id)));
+	assert_eq!(rps.game_of(3, None), Ok(None));
+}
+
+#[test]
+fn game_returns_stored_game() {
+	let game_id = H256::repeat_byte(1);
+	let api = Arc::new(TestApi::new(game_id));
+	let best = api.info().best_hash;
+	let rps = Rps::new(api);
+
+	assert_eq!(rps.game(game_id, None), Ok(Some(TestGame::default())));
+	assert_eq!(rps.game(game_id, Some(best)), Ok(Some(TestGame::default())));
+	assert_eq!(rps.game(H256::repeat_byte(2), None), Ok(None));
+}
+
+#[test]
+fn pending_players_and_deadline_of_running_phase() {
+	let game_id = H256::repeat_byte(1);
+	let rps = Rps::new(Arc::new(TestApi::new(game_id)));
+
+	assert_eq!(rps.pending_players(game_id, None), Ok(vec![2]));
+	assert_eq!(rps.phase_deadline(game_id, None), Ok(Some(11)));
+
+	// unknown games have no running phase
+	assert_eq!(rps.pending_players(H256::repeat_byte(2), None), Ok(vec![]));
+	assert_eq!(rps.phase_deadline(H256::repeat_byte(2), None), Ok(None));
+}
+
+#[test]
+fn failed_runtime_call_is_server_error() {
+	let rps = Rps::new(Arc::new(TestApi::new(H256::repeat_byte(1))));
+
+	let err = rps.game(broken_game(), None).unwrap_err();
+	assert_eq!(err.code, ErrorCode::ServerError(RUNTIME_ERROR));
+	assert_eq!(err.message, "Unable to query the rps runtime api.");
+	assert!(err.data.is_some());
+}
@@ -1,19 +1,291 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarking setup for pallet-rps
 
 use super::*;
 
 use frame_system::RawOrigin;
-use frame_benchmarking::{benchmarks, whitelisted_caller, impl_benchmark_test_suite};
+use frame_support::traits::{EnsureOrigin, Hooks};
+use frame_benchmarking::{benchmarks, account, whitelisted_caller, impl_benchmark_test_suite};
+use sp_runtime::traits::{Bounded, Convert};
+use sp_std::convert::TryFrom;
 #[allow(unused)]
 use crate::Pallet as RockPaperScissor;
 
+const SEED: u32 = 0;
+const SALT: [u8; 32] = [7u8; 32];
+
+/// Gives an account enough funds for stakes and bonds.
+fn fund<T: Config>(account: &T::AccountId) {
+	T::Currency::make_free_balance_be(account, BalanceOf::<T>::max_value() / 2u32.into());
+}
+
+/// Funded player of a game.
+fn player<T: Config>(index: u32) -> T::AccountId {
+	let player: T::AccountId = account("player", index, SEED);
+	fund::<T>(&player);
+	player
+}
+
+/// Stake of wagered games, the worst case of every call.
+fn stake<T: Config>() -> BalanceOf<T> {
+	1_000u32.into()
+}
+
+/// Two weapons of the rule set where the first beats the second.
+fn weapons<T: Config>() -> (WeaponOf<T>, WeaponOf<T>) {
+	let weapons: Vec<WeaponOf<T>> = (0u8..32)
+		.filter_map(|index| WeaponOf::<T>::decode(&mut &[index][..]).ok())
+		.filter(|weapon| T::RuleSet::is_valid(weapon))
+		.collect();
+	for a in &weapons {
+		for b in &weapons {
+			if T::RuleSet::beats(a, b) == Ordering::Greater {
+				return (a.clone(), b.clone());
+			}
+		}
+	}
+	panic!("rule set needs a weapon beating another one");
+}
+
+/// Two unfunded players of a game, for games which are never played.
+fn pair<T: Config>(name: &'static str, index: u32) -> PlayersOf<T> {
+	let players = sp_std::vec![account(name, 2 * index, SEED), account(name, 2 * index + 1, SEED)];
+	PlayersOf::<T>::try_from(players).expect("MaxPlayers is at least two; qed")
+}
+
+/// Creates a wagered game of `amount` players for the calls ending a game, `initiated` lets all
+/// players initiate it.
+fn full_game<T: Config>(
+	amount: u32,
+	initiated: bool
+) -> Result<(T::Hash, Vec<T::AccountId>), &'static str> {
+	let players: Vec<T::AccountId> = (0 .. amount).map(player::<T>).collect();
+	// the stake of the creator is reserved before the game gets created
+	T::Currency::reserve(&players[0], stake::<T>())?;
+	let bounded = PlayersOf::<T>::try_from(players.clone()).map_err(|_| "players exceed MaxPlayers")?;
+	let game_id = RockPaperScissor::<T>::create_game(bounded, 1, stake::<T>());
+	if initiated {
+		for player in &players {
+			RockPaperScissor::<T>::initiate(RawOrigin::Signed(player.clone()).into())?;
+		}
+	}
+	Ok((game_id, players))
+}
+
+/// Number of players the matchmaker puts into a match, found by queueing players until they
+/// get matched.
+fn match_size<T: Config>() -> Result<u32, &'static str> {
+	for index in 0 .. T::MaxPlayers::get() {
+		let player: T::AccountId = account("probe", index, SEED);
+		let bracket = T::BracketOf::convert(RockPaperScissor::<T>::player_rating(&player));
+		T::MatchMaker::add_queue(player, bracket);
+		let matched = T::MatchMaker::try_match();
+		if !matched.is_empty() {
+			return Ok(matched.len() as u32);
+		}
+	}
+	Err("matchmaker matches more than MaxPlayers players")
+}
+
+/// Queues `amount` players named `name`.
+fn queue_players<T: Config>(name: &'static str, amount: u32) {
+	for index in 0 .. amount {
+		let player: T::AccountId = account(name, index, SEED);
+		let bracket = T::BracketOf::convert(RockPaperScissor::<T>::player_rating(&player));
+		T::MatchMaker::add_queue(player, bracket);
+	}
+}
+
+/// Creates a wagered game of two players, `initiated` lets both initiate it.
+fn wagered_game<T: Config>(
+	initiated: bool
+) -> Result<(T::Hash, T::AccountId, T::AccountId), &'static str> {
+	let player_1 = player::<T>(1);
+	let player_2 = player::<T>(2);
+	RockPaperScissor::<T>::new_wagered_game(RawOrigin::Signed(player_1.clone()).into(), player_2.clone(), stake::<T>())?;
+	if initiated {
+		RockPaperScissor::<T>::initiate(RawOrigin::Signed(player_1.clone()).into())?;
+		RockPaperScissor::<T>::initiate(RawOrigin::Signed(player_2.clone()).into())?;
+	}
+	Ok((RockPaperScissor::<T>::player_game(&player_1), player_1, player_2))
+}
+
+/// Commits `weapon` for `player` in the current round.
+fn commit<T: Config>(game_id: T::Hash, player: &T::AccountId, weapon: &WeaponOf<T>) -> DispatchResult {
+	let round = RockPaperScissor::<T>::games(&game_id).round;
+	let choice_hash = RockPaperScissor::<T>::hash_choice(game_id, round, player, SALT, weapon);
+	RockPaperScissor::<T>::choose(RawOrigin::Signed(player.clone()).into(), choice_hash)
+}
+
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	new_game {
+		let caller: T::AccountId = whitelisted_caller();
+		let opponent = player::<T>(1);
+	}: _(RawOrigin::Signed(caller.clone()), opponent)
+	verify {
+		assert!(PlayerGame::<T>::contains_key(&caller));
+	}
+
+	new_wagered_game {
+		let caller: T::AccountId = whitelisted_caller();
+		fund::<T>(&caller);
+		let opponent = player::<T>(1);
+	}: _(RawOrigin::Signed(caller.clone()), opponent, stake::<T>())
+	verify {
+		assert_eq!(T::Currency::reserved_balance(&caller), stake::<T>());
+	}
+
+	new_series {
+		let caller: T::AccountId = whitelisted_caller();
+		let opponent = player::<T>(1);
+	}: _(RawOrigin::Signed(caller.clone()), opponent, 3)
+	verify {
+		assert_eq!(RockPaperScissor::<T>::games(RockPaperScissor::<T>::player_game(&caller)).best_of, 3);
+	}
+
+	queue {
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(T::MatchMaker::is_queued(caller));
+	}
+
+	dequeue {
+		let caller: T::AccountId = whitelisted_caller();
+		RockPaperScissor::<T>::queue(RawOrigin::Signed(caller.clone()).into())?;
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!T::MatchMaker::is_queued(caller));
+	}
+
+	empty_queue {
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		RockPaperScissor::<T>::queue(RawOrigin::Signed(caller.clone()).into())?;
+		let origin = T::AdminOrigin::successful_origin();
+	}: {
+		RockPaperScissor::<T>::empty_queue(origin)?;
+	}
+	verify {
+		assert!(!T::MatchMaker::is_queued(caller));
+	}
+
+	// last player initiating a wagered game, which starts the choose phase
+	initiate {
+		let (game_id, player_1, player_2) = wagered_game::<T>(false)?;
+		RockPaperScissor::<T>::initiate(RawOrigin::Signed(player_1).into())?;
+	}: _(RawOrigin::Signed(player_2))
+	verify {
+		assert!(matches!(RockPaperScissor::<T>::games(&game_id).match_state, MatchState::Choose(_)));
+	}
+
+	// last player choosing, which starts the reveal phase
+	choose {
+		let (game_id, player_1, player_2) = wagered_game::<T>(true)?;
+		let (weapon, _) = weapons::<T>();
+		commit::<T>(game_id, &player_1, &weapon)?;
+		let round = RockPaperScissor::<T>::games(&game_id).round;
+		let choice_hash = RockPaperScissor::<T>::hash_choice(game_id, round, &player_2, SALT, &weapon);
+	}: _(RawOrigin::Signed(player_2), choice_hash)
+	verify {
+		assert!(matches!(RockPaperScissor::<T>::games(&game_id).match_state, MatchState::Reveal(_)));
+	}
+
+	// last player revealing, which evaluates the round and settles the stakes of all losers
+	reveal {
+		let p in 2 .. T::MaxPlayers::get();
+		let (game_id, mut players) = full_game::<T>(p, true)?;
+		let (winning, losing) = weapons::<T>();
+		let winner = players.pop().expect("games have at least two players; qed");
+		for player in &players {
+			commit::<T>(game_id, player, &losing)?;
+		}
+		commit::<T>(game_id, &winner, &winning)?;
+		for player in &players {
+			RockPaperScissor::<T>::reveal(RawOrigin::Signed(player.clone()).into(), losing.clone(), SALT)?;
+		}
+	}: _(RawOrigin::Signed(winner.clone()), winning, SALT)
+	verify {
+		assert_eq!(
+			RockPaperScissor::<T>::games(&game_id).match_state,
+			MatchState::Finished(GameResult::Winner(winner))
+		);
+	}
+
+	// aborting a wagered game in the choose phase returns stakes and bonds of all players
+	abort_game {
+		let p in 2 .. T::MaxPlayers::get();
+		let (game_id, _) = full_game::<T>(p, true)?;
+		let origin = T::AdminOrigin::successful_origin();
+	}: {
+		RockPaperScissor::<T>::abort_game(origin, game_id)?;
+	}
+	verify {
+		assert_eq!(
+			RockPaperScissor::<T>::games(&game_id).match_state,
+			MatchState::Finished(GameResult::Abandoned)
+		);
+	}
+
+	// resuming play reschedules the timeouts of `g` running games
+	set_paused {
+		let g in 0 .. 100;
+		for index in 0 .. g {
+			RockPaperScissor::<T>::create_game(pair::<T>("player", index), 1, Zero::zero());
+		}
+		Paused::<T>::put(true);
+		let origin = T::AdminOrigin::successful_origin();
+	}: {
+		RockPaperScissor::<T>::set_paused(origin, false, g)?;
+	}
+	verify {
+		assert!(!RockPaperScissor::<T>::paused());
+	}
+
+	// all players but one not revealing in time, they lose the wagered game and their bonds
+	force_timeout {
+		let p in 2 .. T::MaxPlayers::get();
+		let (game_id, players) = full_game::<T>(p, true)?;
+		let (winning, losing) = weapons::<T>();
+		let winner = players[0].clone();
+		commit::<T>(game_id, &winner, &winning)?;
+		for player in &players[1..] {
+			commit::<T>(game_id, player, &losing)?;
+		}
+		RockPaperScissor::<T>::reveal(RawOrigin::Signed(winner.clone()).into(), winning, SALT)?;
+	}: _(RawOrigin::Root, game_id)
+	verify {
+		assert_eq!(
+			RockPaperScissor::<T>::games(&game_id).match_state,
+			MatchState::Finished(GameResult::Forfeit(winner))
+		);
+	}
+
+	// `m` matches of queued players, `c` finished games to remove and `d` players who left the
+	// queue ahead of the matched players
+	on_initialize {
+		let m in 0 .. T::MaxGamesPerBlock::get();
+		let c in 0 .. T::MaxGamesPerBlock::get();
+		let d in 0 .. T::MaxDroppedPerBlock::get();
+
+		let block_number = <frame_system::Pallet<T>>::block_number() + 1u32.into();
+		for index in 0 .. c {
+			let game_id = RockPaperScissor::<T>::create_game(pair::<T>("finished", index), 1, Zero::zero());
+			RockPaperScissor::<T>::schedule_cleanup(game_id, block_number);
+		}
+		let size = match_size::<T>()?;
+		queue_players::<T>("dequeued", d);
+		for index in 0 .. d {
+			T::MatchMaker::remove_queue(account("dequeued", index, SEED));
+		}
+		queue_players::<T>("queued", m * size);
+	}: {
+		RockPaperScissor::<T>::on_initialize(block_number);
+	}
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(GameCleanup::<T>::get(block_number).is_empty());
+		for index in 0 .. m * size {
+			let player: T::AccountId = account("queued", index, SEED);
+			assert!(PlayerGame::<T>::contains_key(&player));
+		}
 	}
 }
 
@@ -21,4 +293,4 @@ impl_benchmark_test_suite!(
 	RockPaperScissor,
 	crate::mock::new_test_ext(),
 	crate::mock::Test,
-);
\ No newline at end of file
+);
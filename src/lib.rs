@@ -7,22 +7,27 @@
 pub use pallet::*;
 
 use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 use frame_support::{
-	log,
-	traits::{Randomness, LockIdentifier, schedule::{Named, DispatchTime}},
-};
-use frame_system::{
-	WeightInfo
+	ensure, log, BoundedVec, CloneNoBound, PartialEqNoBound, DebugNoBound,
+	dispatch::DispatchResult,
+	traits::{
+		Get, Randomness, LockIdentifier, Currency, ReservableCurrency, BalanceStatus,
+		Imbalance, OnUnbalanced, MaxEncodedLen,
+		schedule::{Named, DispatchTime},
+	},
 };
 use sp_runtime::{
-	traits::{Hash, Dispatchable, TrailingZeroInput}
+	Perbill, PerThing,
+	traits::{Hash, Dispatchable, TrailingZeroInput, Saturating, Zero, Convert}
 };
-use sp_std::vec::{
-	Vec
+use sp_std::{
+	cmp::Ordering,
+	convert::TryFrom,
+	fmt::Debug,
+	vec::Vec,
 };
-use sp_io::hashing::blake2_256;
-
-use pallet_matchmaker::MatchFunc;
 
 use log::info;
 
@@ -35,44 +40,250 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
-#[derive(Encode, Decode, Clone, PartialEq, Debug)]
-pub enum MatchState<AccountId> {
+pub mod migrations;
+
+pub mod rules;
+use rules::{RoundOutcome, RuleSet};
+
+pub mod rating;
+use rating::{Rating, SCORE_ONE, SCORE_DRAW};
+
+pub mod matchmaking;
+use matchmaking::MatchQueue;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+/// Serde of bounded vecs as plain sequences, the bound is checked when deserializing.
+#[cfg(feature = "std")]
+mod bounded_serde {
+	use super::*;
+	use serde::{de::Error, Deserializer, Serializer};
+
+	pub fn serialize<T, S, Ser>(items: &BoundedVec<T, S>, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+	where T: Serialize, S: Get<u32>, Ser: Serializer {
+		items[..].serialize(serializer)
+	}
+
+	pub fn deserialize<'de, T, S, D>(deserializer: D) -> Result<BoundedVec<T, S>, D::Error>
+	where T: Deserialize<'de>, S: Get<u32>, D: Deserializer<'de> {
+		let items = Vec::<T>::deserialize(deserializer)?;
+		BoundedVec::try_from(items).map_err(|_| D::Error::custom("too many items"))
+	}
+}
+
+/// State of a game, running phases hold the players who still have to act.
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, DebugNoBound)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum MatchState<AccountId: Clone + PartialEq + Debug, MaxPlayers: Get<u32>> {
+	None,
+	Initiate(#[cfg_attr(feature = "std", serde(with = "bounded_serde"))] BoundedVec<AccountId, MaxPlayers>),
+	Choose(#[cfg_attr(feature = "std", serde(with = "bounded_serde"))] BoundedVec<AccountId, MaxPlayers>),
+	Reveal(#[cfg_attr(feature = "std", serde(with = "bounded_serde"))] BoundedVec<AccountId, MaxPlayers>),
+	Finished(GameResult<AccountId>),
+}
+impl<AccountId: Clone + PartialEq + Debug, MaxPlayers: Get<u32>> Default for MatchState<AccountId, MaxPlayers> {
+	fn default() -> Self { Self::None }
+}
+// the derive would require `MaxPlayers: MaxEncodedLen`
+impl<AccountId, MaxPlayers> MaxEncodedLen for MatchState<AccountId, MaxPlayers>
+where
+	AccountId: Clone + PartialEq + Debug + MaxEncodedLen,
+	MaxPlayers: Get<u32>,
+{
+	fn max_encoded_len() -> usize {
+		// variant index and the largest variant
+		BoundedVec::<AccountId, MaxPlayers>::max_encoded_len()
+			.max(GameResult::<AccountId>::max_encoded_len())
+			.saturating_add(1)
+	}
+}
+impl<AccountId: Clone + PartialEq + Debug, MaxPlayers: Get<u32>> MatchState<AccountId, MaxPlayers> {
+	/// Phase of the match state, without the players who still have to act.
+	pub fn phase(&self) -> Phase {
+		match self {
+			Self::None => Phase::None,
+			Self::Initiate(_) => Phase::Initiate,
+			Self::Choose(_) => Phase::Choose,
+			Self::Reveal(_) => Phase::Reveal,
+			Self::Finished(_) => Phase::Finished,
+		}
+	}
+}
+
+/// Storage layout versions of the pallet, see the `migrations` module.
+#[derive(Encode, Decode, MaxEncodedLen, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Releases {
+	/// Initial layout, with the `Something` storage of the node template.
+	V1,
+	/// `Something` removed.
+	V2,
+	/// Games with stakes, bonds, rounds and results, choices with the weapon of the rule set
+	/// and commitments bound to game, round and player.
+	V3,
+}
+impl Default for Releases { fn default() -> Self { Self::V1 } }
+
+#[derive(Encode, Decode, MaxEncodedLen, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Phase {
 	None,
-	Initiate(Vec<AccountId>),
-	Choose(Vec<AccountId>),
-	Reveal(Vec<AccountId>),
-	Finished(AccountId),
+	Initiate,
+	Choose,
+	Reveal,
+	Finished,
+}
+
+#[derive(Encode, Decode, MaxEncodedLen, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum GameResult<AccountId> {
+	/// Player won the game.
+	Winner(AccountId),
+	/// No player could beat the others.
+	Draw,
+	/// No player acted in time.
+	Abandoned,
+	/// Player won, as the opponents didn't act in time.
+	Forfeit(AccountId),
 }
-impl<AccountId> Default for MatchState<AccountId> { fn default() -> Self { Self::None } }
 
-#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+#[derive(Encode, Decode, MaxEncodedLen, Clone, PartialEq, Eq, Debug)]
 pub enum WeaponType {
 	None,
 	Rock,
 	Paper,
 	Scissor,
+	Lizard,
+	Spock,
 }
 impl Default for WeaponType { fn default() -> Self { Self::None } }
 
-#[derive(Encode, Decode, Clone, PartialEq, Debug)]
-pub enum Choice<Hash> {
+#[derive(Encode, Decode, MaxEncodedLen, Clone, PartialEq, Debug)]
+pub enum Choice<Hash, Weapon> {
 	None,
 	Choose(Hash),
-	Reveal(WeaponType),
+	Reveal(Weapon),
 }
-impl<Hash> Default for Choice<Hash> { fn default() -> Self { Self::None } }
+impl<Hash, Weapon> Default for Choice<Hash, Weapon> { fn default() -> Self { Self::None } }
+
+/// Weapon of the rule set used by the runtime.
+pub type WeaponOf<T> = <<T as Config>::RuleSet as RuleSet>::Weapon;
+
+/// Balance of the currency used for stakes.
+pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Negative imbalance of the currency used for stakes.
+pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
+
+/// Game as stored by the runtime.
+pub type GameOf<T> = Game<
+	<T as frame_system::Config>::Hash,
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+	BalanceOf<T>,
+	<T as Config>::MaxPlayers,
+>;
+
+/// Players of a game, or a part of them.
+pub type PlayersOf<T> = BoundedVec<<T as frame_system::Config>::AccountId, <T as Config>::MaxPlayers>;
+
+/// Match state as stored by the runtime.
+pub type MatchStateOf<T> = MatchState<<T as frame_system::Config>::AccountId, <T as Config>::MaxPlayers>;
 
 /// Connect four board structure containing two players and the board
-#[derive(Encode, Decode, Default, Clone, PartialEq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct Game<Hash, AccountId, BlockNumber> {
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, DebugNoBound)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(bound(
+	serialize = "Hash: Serialize, AccountId: Serialize, BlockNumber: Serialize, Balance: Serialize",
+	deserialize = "Hash: Deserialize<'de>, AccountId: Deserialize<'de>, BlockNumber: Deserialize<'de>, Balance: Deserialize<'de>",
+)))]
+pub struct Game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>
+where
+	Hash: Clone + PartialEq + Debug,
+	AccountId: Clone + PartialEq + Debug,
+	BlockNumber: Clone + PartialEq + Debug,
+	Balance: Clone + PartialEq + Debug,
+	MaxPlayers: Get<u32>,
+{
 	id: Hash,
-	players: Vec<AccountId>,
+	#[cfg_attr(feature = "std", serde(with = "bounded_serde"))]
+	players: BoundedVec<AccountId, MaxPlayers>,
+	/// Amount each player bets on the game, the winner takes all stakes.
+	stake: Balance,
+	/// Players whose stake is reserved.
+	#[cfg_attr(feature = "std", serde(with = "bounded_serde"))]
+	staked: BoundedVec<AccountId, MaxPlayers>,
+	/// Participation bond of each player, slashed if the player doesn't act in time.
+	bond: Balance,
+	/// Players whose participation bond is reserved.
+	#[cfg_attr(feature = "std", serde(with = "bounded_serde"))]
+	bonded: BoundedVec<AccountId, MaxPlayers>,
+	/// Choose rounds played so far, players not eliminated choose again each round.
+	round: u32,
+	/// Amount of rounds of the series, a player needs to win the majority of them.
+	best_of: u8,
+	/// Rounds each player has won so far.
+	#[cfg_attr(feature = "std", serde(with = "bounded_serde"))]
+	scores: BoundedVec<(AccountId, u8), MaxPlayers>,
+	/// Drawn rounds which got replayed.
+	redraws: u8,
 	last_action: BlockNumber,
-	match_state: MatchState<AccountId>,
+	match_state: MatchState<AccountId, MaxPlayers>,
+}
+impl<Hash, AccountId, BlockNumber, Balance, MaxPlayers> Default for Game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>
+where
+	Hash: Clone + PartialEq + Debug + Default,
+	AccountId: Clone + PartialEq + Debug,
+	BlockNumber: Clone + PartialEq + Debug + Default,
+	Balance: Clone + PartialEq + Debug + Default,
+	MaxPlayers: Get<u32>,
+{
+	fn default() -> Self {
+		Self {
+			id: Default::default(),
+			players: Default::default(),
+			stake: Default::default(),
+			staked: Default::default(),
+			bond: Default::default(),
+			bonded: Default::default(),
+			round: 0,
+			best_of: 0,
+			scores: Default::default(),
+			redraws: 0,
+			last_action: Default::default(),
+			match_state: MatchState::None,
+		}
+	}
+}
+// the derive would require `MaxPlayers: MaxEncodedLen`
+impl<Hash, AccountId, BlockNumber, Balance, MaxPlayers> MaxEncodedLen for Game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>
+where
+	Hash: Clone + PartialEq + Debug + MaxEncodedLen,
+	AccountId: Clone + PartialEq + Debug + MaxEncodedLen,
+	BlockNumber: Clone + PartialEq + Debug + MaxEncodedLen,
+	Balance: Clone + PartialEq + Debug + MaxEncodedLen,
+	MaxPlayers: Get<u32>,
+{
+	fn max_encoded_len() -> usize {
+		Hash::max_encoded_len()
+			// players, staked and bonded
+			.saturating_add(BoundedVec::<AccountId, MaxPlayers>::max_encoded_len().saturating_mul(3))
+			// stake and bond
+			.saturating_add(Balance::max_encoded_len().saturating_mul(2))
+			// round, best_of and redraws
+			.saturating_add(u32::max_encoded_len())
+			.saturating_add(u8::max_encoded_len().saturating_mul(2))
+			.saturating_add(BoundedVec::<(AccountId, u8), MaxPlayers>::max_encoded_len())
+			.saturating_add(BlockNumber::max_encoded_len())
+			.saturating_add(MatchState::<AccountId, MaxPlayers>::max_encoded_len())
+	}
 }
 
-const MAX_GAMES_PER_BLOCK: u8 = 10;
+/// Prefix of the named scheduler entries of the games.
+const RPS_ID: LockIdentifier = *b"rockpapr";
+
+/// Version tag of the commitment preimage, needs to change with the preimage layout.
+const COMMITMENT_VERSION: [u8; 8] = *b"rps/v003";
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -92,8 +303,98 @@ pub mod pallet {
 		/// The generator used to supply randomness to contracts through `seal_random`.
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 
-		/// Jton matchmaker pallet for match making.
-		type MatchMaker: MatchFunc<Self::AccountId>;
+		/// Jton matchmaker pallet for match making, wrapped in `matchmaking::Dequeueing` to let
+		/// players leave the queue.
+		type MatchMaker: MatchQueue<Self::AccountId>;
+
+		/// Origin allowed to administrate the pallet, like emptying the queue.
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Rule set with the weapons players can choose and which one beats which.
+		type RuleSet: RuleSet;
+
+		/// Currency players bet with in wagered games.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Bond reserved from each player initiating a game, returned when the game finishes.
+		/// Players who don't choose or reveal in time, or don't initiate a later round of a
+		/// series, lose their bond.
+		#[pallet::constant]
+		type ParticipationBond: Get<BalanceOf<Self>>;
+
+		/// Handler for slashed bonds of games which end without a winner to receive them. Bonds
+		/// slashed while the game goes on are shared by the players who acted in time.
+		type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// Cut of the pot of wagered games taken by the house, only taken once a loser staked.
+		#[pallet::constant]
+		type HouseFee: Get<Perbill>;
+
+		/// Receiver of the house fee, like a treasury.
+		type FeeDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// Call which can be dispatched by the scheduler.
+		type Proposal: Parameter + Dispatchable<Origin=Self::Origin> + From<Call<Self>>;
+
+		/// Scheduler used to end a game phase when players don't act in time.
+		type Scheduler: Named<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+
+		/// Overarching type of all pallets origins.
+		type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+
+		/// Blocks players have to initiate a created game, needs to be at least one.
+		#[pallet::constant]
+		type InitiateTimeout: Get<Self::BlockNumber>;
+
+		/// Blocks players have to commit their choice, needs to be at least one.
+		#[pallet::constant]
+		type ChooseTimeout: Get<Self::BlockNumber>;
+
+		/// Blocks players have to reveal their choice, needs to be at least one.
+		#[pallet::constant]
+		type RevealTimeout: Get<Self::BlockNumber>;
+
+		/// Drawn single games get replayed by the players of the drawn round.
+		#[pallet::constant]
+		type RematchOnDraw: Get<bool>;
+
+		/// Amount of replays of drawn single games, before the game ends in a draw.
+		#[pallet::constant]
+		type MaxRedraws: Get<u8>;
+
+		/// Blocks a finished game is kept in storage, zero removes it immediately.
+		#[pallet::constant]
+		type CleanupDelay: Get<Self::BlockNumber>;
+
+		/// Maximum amount of players of a game, needs to be at least two and at least the
+		/// amount of players the matchmaker matches.
+		#[pallet::constant]
+		type MaxPlayers: Get<u32>;
+
+		/// Maximum amount of games created by the matchmaker and of finished games removed in
+		/// a block, needs to be at least one. Finished games beyond it are removed a block later.
+		#[pallet::constant]
+		type MaxGamesPerBlock: Get<u32>;
+
+		/// Maximum amount of players who left the queue dropped from matches in a block, needs
+		/// to be at least one. Matching continues in the next block once it's reached.
+		#[pallet::constant]
+		type MaxDroppedPerBlock: Get<u32>;
+
+		/// Maximum rating points a player wins or loses against a single opponent.
+		#[pallet::constant]
+		type KFactor: Get<u32>;
+
+		/// Rating of players who haven't finished a rated game yet.
+		#[pallet::constant]
+		type StartingRating: Get<Rating>;
+
+		/// Matchmaker bracket of players with a rating, needs to be below the amount of
+		/// brackets of the matchmaker. Queued players are only matched within their bracket.
+		type BracketOf: Convert<Rating, u8>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
@@ -103,14 +404,9 @@ pub mod pallet {
 	// The pallet's runtime storage items.
 	// https://substrate.dev/docs/en/knowledgebase/runtime/storage
 	#[pallet::storage]
-	#[pallet::getter(fn something)]
-	// Learn more about declaring storage items:
-	// https://substrate.dev/docs/en/knowledgebase/runtime/storage#declaring-storage-items
-	pub type Something<T> = StorageValue<_, u32>;
-
-	#[pallet::storage]
-	#[pallet::getter(fn founder_key)]
-	pub type FounderKey<T: Config> = StorageValue<_, T::AccountId>;
+	#[pallet::getter(fn storage_version)]
+	/// Storage layout version, updated by the migrations.
+	pub type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
 
 	// Default value for Nonce
 	#[pallet::type_value]
@@ -122,7 +418,7 @@ pub mod pallet {
 	#[pallet::storage]
 	#[pallet::getter(fn games)]
 	/// Store all games that are currently being played.
-	pub type Games<T: Config> = StorageMap<_, Identity, T::Hash, Game<T::Hash, T::AccountId, T::BlockNumber>, ValueQuery>;
+	pub type Games<T: Config> = StorageMap<_, Identity, T::Hash, GameOf<T>, ValueQuery>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn player_game)]
@@ -132,56 +428,112 @@ pub mod pallet {
 	#[pallet::storage]
 	#[pallet::getter(fn player_choice)]
 	/// Player choices of each game.
-	pub type PlayerChoice<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::Hash, Blake2_128Concat, T::AccountId, Choice<T::Hash>, ValueQuery>;
+	pub type PlayerChoice<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::Hash, Blake2_128Concat, T::AccountId, Choice<T::Hash, WeaponOf<T>>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn game_cleanup)]
+	/// Finished games to be removed at the beginning of a block.
+	pub type GameCleanup<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<T::Hash, T::MaxGamesPerBlock>, ValueQuery>;
+
+	// Default value for PlayerRating
+	#[pallet::type_value]
+	pub fn RatingDefault<T: Config>() -> Rating { T::StartingRating::get() }
+	#[pallet::storage]
+	#[pallet::getter(fn player_rating)]
+	/// Elo rating of each player, updated when a game finishes.
+	pub type PlayerRating<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, Rating, ValueQuery, RatingDefault<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn phase_deadline)]
+	/// Block at which the current phase of a running game times out.
+	pub type PhaseDeadline<T: Config> = StorageMap<_, Identity, T::Hash, T::BlockNumber>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn running_games)]
+	/// Number of games which didn't finish yet, resuming play reschedules their timeouts.
+	pub type RunningGames<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	/// Play is paused, no games can be created and no moves made. Timeouts of running games
+	/// are frozen, they restart with a full phase once play is resumed.
+	pub type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn dequeued)]
+	/// Players who left the queue with their bracket, dropped from the matchmaker once matched.
+	pub type Dequeued<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u8>;
 
 	// The genesis config type.
 	#[pallet::genesis_config]
-	pub struct GenesisConfig<T: Config> {
-		pub founder_key: T::AccountId,
-	}
+	pub struct GenesisConfig {}
 
 	// The default value for the genesis config type.
 	#[cfg(feature = "std")]
-	impl<T: Config> Default for GenesisConfig<T> {
+	impl Default for GenesisConfig {
 		fn default() -> Self {
-			Self {
-				founder_key: Default::default(),
-			}
+			Self {}
 		}
 	}
 
 	// The build of genesis for the pallet.
 	#[pallet::genesis_build]
-	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
 		fn build(&self) {
-			<FounderKey<T>>::put(&self.founder_key);
+			// new chains start with the current storage layout
+			StorageVersion::<T>::put(Releases::V3);
 		}
 	}
 
 	// Pallets use events to inform users when important changes are made.
 	// https://substrate.dev/docs/en/knowledgebase/runtime/events
 	#[pallet::event]
-	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::metadata(T::AccountId = "AccountId", BalanceOf<T> = "Balance", WeaponOf<T> = "Weapon")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Event documentation should end with an array that provides descriptive names for event
-		/// parameters. [something, who]
-		SomethingStored(u32, T::AccountId),
-		/// A new game got created.
-		NewGame(T::Hash),
-		/// A games match state changed.
-		MatchStateChange(T::Hash, MatchState<T::AccountId>),
+		/// A new game got created. \[game_id, players, best_of, stake\]
+		GameCreated(T::Hash, Vec<T::AccountId>, u8, BalanceOf<T>),
+		/// A player initiated a game. \[game_id, player\]
+		PlayerInitiated(T::Hash, T::AccountId),
+		/// A player committed to a weapon. \[game_id, player, commitment\]
+		PlayerCommitted(T::Hash, T::AccountId, T::Hash),
+		/// A player revealed the weapon committed to. \[game_id, player, weapon\]
+		PlayerRevealed(T::Hash, T::AccountId, WeaponOf<T>),
+		/// A game moved on to another phase, or restarted its phase. \[game_id, from, to\]
+		PhaseAdvanced(T::Hash, Phase, Phase),
+		/// A game finished with a result. \[game_id, result\]
+		GameFinished(T::Hash, GameResult<T::AccountId>),
+		/// A round of a series got won by a player. \[game_id, round, winner\]
+		RoundWon(T::Hash, u32, T::AccountId),
+		/// A round ended in a draw and gets replayed. \[game_id, round\]
+		RoundDrawn(T::Hash, u32),
+		/// Stake of a player got reserved. \[game_id, player, amount\]
+		StakeReserved(T::Hash, T::AccountId, BalanceOf<T>),
+		/// Reserved stake got returned to a player. \[game_id, player, amount\]
+		StakeReleased(T::Hash, T::AccountId, BalanceOf<T>),
+		/// Reserved stake of a player got paid to the winner. \[game_id, from, to, amount\]
+		StakePaid(T::Hash, T::AccountId, T::AccountId, BalanceOf<T>),
+		/// Pot of a wagered game got paid to the winner, after the house fee. \[game_id, winner, payout, fee\]
+		GameSettled(T::Hash, T::AccountId, BalanceOf<T>, BalanceOf<T>),
+		/// Participation bond of a player got reserved. \[game_id, player, amount\]
+		BondReserved(T::Hash, T::AccountId, BalanceOf<T>),
+		/// Participation bond got returned to a player. \[game_id, player, amount\]
+		BondReleased(T::Hash, T::AccountId, BalanceOf<T>),
+		/// Participation bond of a player who didn't act in time got slashed. \[game_id, player, amount\]
+		BondSlashed(T::Hash, T::AccountId, BalanceOf<T>),
+		/// A finished game got removed and its players are free again.
+		GameRemoved(T::Hash),
+		/// Rating of a player got updated after a game. \[player, old_rating, new_rating\]
+		RatingUpdated(T::AccountId, Rating, Rating),
+		/// Play got paused by the admin.
+		Paused,
+		/// Play got resumed by the admin, running games got new timeouts.
+		Resumed,
 	}
 
 	// Errors inform users that something went wrong.
 	#[pallet::error]
 	pub enum Error<T> {
-		/// Error names should be descriptive.
-		NoneValue,
-		/// Errors should have helpful documentation associated with them.
-		StorageOverflow,
-		/// Only founder is allowed to do this.
-		OnlyFounderAllowed,
 		/// Player can't play against them self.
 		NoFakePlay,
 		/// Player has already a game.
@@ -196,6 +548,26 @@ pub mod pallet {
 		BadBehaviour,
 		/// Player is already queued.
 		AlreadyQueued,
+		/// Player is not queued.
+		NotQueued,
+		/// Game has no running phase which could time out.
+		NoRunningPhase,
+		/// A series needs at least one round.
+		InvalidRounds,
+		/// Weapon is not part of the rule set.
+		InvalidWeapon,
+		/// Player can't afford the stake.
+		InsufficientBalance,
+		/// Play is paused.
+		Paused,
+		/// Game would have more than `MaxPlayers` players.
+		TooManyPlayers,
+		/// Witness is lower than the number of running games.
+		InvalidWitness,
+		/// Player left the queue of another bracket and wasn't dropped from it yet.
+		StaleQueueEntry,
+		/// A wagered game needs a stake.
+		InvalidStake,
 	}
 
 	#[pallet::hooks]
@@ -204,28 +576,55 @@ pub mod pallet {
 		// dispatched.
 		//
 		// This function must return the weight consumed by `on_initialize` and `on_finalize`.
-		fn on_initialize(_: T::BlockNumber) -> Weight {
-			// Anything that needs to be done at the start of the block.
-			// We don't do anything here.
-			
-			// initial weights
-			let mut tot_weights = 10_000;
-			for _i in 0..MAX_GAMES_PER_BLOCK {
-				// try to create a match till we reached max games or no more matches available
-				let result = T::MatchMaker::try_match();
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			// remove finished games which reached the end of their grace period
+			let mut cleanups: u32 = 0;
+			for game_id in GameCleanup::<T>::take(n).into_inner() {
+				Self::remove_game(game_id);
+				cleanups = cleanups + 1;
+			}
+
+			// no new matches while play is paused
+			let mut matches: u32 = 0;
+			let mut dropped: u32 = 0;
+			if Self::paused() {
+				return T::WeightInfo::on_initialize(matches, cleanups, dropped)
+			}
+			let max_dropped = T::MaxDroppedPerBlock::get();
+			for _i in 0..T::MaxGamesPerBlock::get() {
+				// try to create a match till we reached max games or no more matches available,
+				// players who left the queue are dropped from the matches
+				let (result, left) = T::MatchMaker::try_match_dropping(max_dropped.saturating_sub(dropped));
+				dropped = dropped.saturating_add(left);
 				// if result is not empty we have a valid match
-				if !result.is_empty() {
-					// Create new game
-					let _game_id = Self::create_game(result);
-					// weights need to be adjusted
-					tot_weights = tot_weights + T::DbWeight::get().reads_writes(1,1);
-					continue;
+				if result.is_empty() {
+					break;
+				}
+				match PlayersOf::<T>::try_from(result.clone()) {
+					Ok(players) => {
+						// Create new game
+						let _game_id = Self::create_game(players, 1, Zero::zero());
+						matches = matches + 1;
+					},
+					Err(_) => {
+						// the matchmaker matches more players than a game can have, they keep their place
+						log::error!("rps: match of {} players exceeds MaxPlayers", result.len());
+						for player in result {
+							let bracket = T::BracketOf::convert(Self::player_rating(&player));
+							T::MatchMaker::add_queue(player, bracket);
+						}
+						break;
+					},
 				}
-				break;
 			}
 
-			// return standard weigth for trying to fiond a match
-			return tot_weights
+			T::WeightInfo::on_initialize(matches, cleanups, dropped)
+		}
+
+		fn integrity_test() {
+			assert!(T::MaxPlayers::get() >= 2, "games need room for two players");
+			assert!(T::MaxGamesPerBlock::get() >= 1, "finished games need room in a cleanup block");
+			assert!(T::MaxDroppedPerBlock::get() >= 1, "players who left the queue need to be dropped");
 		}
 
 		// `on_finalize` is executed at the end of block after all extrinsic are dispatched.
@@ -250,79 +649,95 @@ pub mod pallet {
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T:Config> Pallet<T> {
-		/// An example dispatchable that takes a singles value as a parameter, writes the value to
-		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn do_something(origin: OriginFor<T>, something: u32) -> DispatchResult {
-			// Check that the extrinsic was signed and get the signer.
-			// This function will return an error if the extrinsic is not signed.
-			// https://substrate.dev/docs/en/knowledgebase/runtime/origin
-			let who = ensure_signed(origin)?;
-
-			// Update storage.
-			<Something<T>>::put(something);
-
-			// Emit an event.
-			Self::deposit_event(Event::SomethingStored(something, who));
-			// Return a successful DispatchResultWithPostInfo
+		/// Create game for two players
+		#[pallet::weight(T::WeightInfo::new_game())]
+		pub fn new_game(origin: OriginFor<T>, opponent: T::AccountId) -> DispatchResult {
+			
+			let sender = ensure_signed(origin)?;
+
+			// Make sure play isn't paused.
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
+			Self::ensure_can_play(&sender, &opponent)?;
+			
+			let players = PlayersOf::<T>::try_from(sp_std::vec![sender.clone(), opponent])
+				.map_err(|_| Error::<T>::TooManyPlayers)?;
+
+			// Create new game
+			let _game_id = Self::create_game(players, 1, Zero::zero());
+
 			Ok(())
 		}
 
-		/// An example dispatchable that may throw a custom error.
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,1))]
-		pub fn cause_error(origin: OriginFor<T>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			// Read a value from storage.
-			match <Something<T>>::get() {
-				// Return an error if the value has not been set.
-				None => Err(Error::<T>::NoneValue)?,
-				Some(old) => {
-					// Increment the value read from storage; will error in the event of overflow.
-					let new = old.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
-					// Update the value in storage with the incremented result.
-					<Something<T>>::put(new);
-					Ok(())
-				},
-			}
-		}
+		/// Create game for two players, where each player bets `stake`. The stake of the sender
+		/// is reserved now, the stake of the opponent once they initiate the game. The winner
+		/// takes all stakes, on a draw or an abandoned game every player gets the stake back.
+		/// The stake can't be zero, games without a stake are created with `new_game`.
+		#[pallet::weight(T::WeightInfo::new_wagered_game())]
+		pub fn new_wagered_game(origin: OriginFor<T>, opponent: T::AccountId, stake: BalanceOf<T>) -> DispatchResult {
 
-		/// Create game for two players
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,1))]
-		pub fn new_game(origin: OriginFor<T>, opponent: T::AccountId) -> DispatchResult {
-			
 			let sender = ensure_signed(origin)?;
 
-			// Don't allow playing against yourself.
-			ensure!(sender != opponent, Error::<T>::NoFakePlay);
+			// Make sure play isn't paused.
+			ensure!(!Self::paused(), Error::<T>::Paused);
 
-			// Don't allow queued player to create a game.
-			ensure!(!T::MatchMaker::is_queued(sender.clone()), Error::<T>::AlreadyQueued);
-			ensure!(!T::MatchMaker::is_queued(opponent.clone()), Error::<T>::AlreadyQueued);
+			ensure!(!stake.is_zero(), Error::<T>::InvalidStake);
 
-			// Make sure players have no board open.
-			ensure!(!PlayerGame::<T>::contains_key(&sender), Error::<T>::PlayerHasGame);
-			ensure!(!PlayerGame::<T>::contains_key(&opponent), Error::<T>::PlayerHasGame);
-			
-			let mut players = Vec::new();
-			players.push(sender.clone());
-			players.push(opponent.clone());
+			Self::ensure_can_play(&sender, &opponent)?;
+
+			let players = PlayersOf::<T>::try_from(sp_std::vec![sender.clone(), opponent])
+				.map_err(|_| Error::<T>::TooManyPlayers)?;
+
+			// Reserve the stake of the sender.
+			T::Currency::reserve(&sender, stake).map_err(|_| Error::<T>::InsufficientBalance)?;
 
 			// Create new game
-			let _game_id = Self::create_game(players);
+			let game_id = Self::create_game(players, 1, stake);
+
+			Self::deposit_event(Event::StakeReserved(game_id, sender, stake));
 
 			Ok(())
 		}
 
-		/// Queue sender up for a game, ranking brackets
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,1))]
+		/// Create a best of `best_of` series for two players, the first player winning the
+		/// majority of the rounds wins the series. Draw rounds get replayed.
+		#[pallet::weight(T::WeightInfo::new_series())]
+		pub fn new_series(origin: OriginFor<T>, opponent: T::AccountId, best_of: u8) -> DispatchResult {
+
+			let sender = ensure_signed(origin)?;
+
+			// Make sure play isn't paused.
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
+			ensure!(best_of > 0, Error::<T>::InvalidRounds);
+
+			Self::ensure_can_play(&sender, &opponent)?;
+
+			let players = PlayersOf::<T>::try_from(sp_std::vec![sender.clone(), opponent])
+				.map_err(|_| Error::<T>::TooManyPlayers)?;
+
+			// Create new series
+			let _game_id = Self::create_game(players, best_of, Zero::zero());
+
+			Ok(())
+		}
+
+		/// Queue sender up for a game, the sender is matched with players of the same rating bracket.
+		#[pallet::weight(T::WeightInfo::queue())]
 		pub fn queue(origin: OriginFor<T>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
+			// Make sure play isn't paused.
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
 			// Make sure player has no board open.
 			ensure!(!PlayerGame::<T>::contains_key(&sender), Error::<T>::PlayerHasGame);
 
-			let bracket: u8 = 0;
+			let bracket = T::BracketOf::convert(Self::player_rating(&sender));
+			// Players who left the queue of another bracket wait till they got dropped from it.
+			if let Some(left) = Self::dequeued(&sender) {
+				ensure!(left == bracket, Error::<T>::StaleQueueEntry);
+			}
 			// Add player to queue, duplicate check is done in matchmaker.
 			if !T::MatchMaker::add_queue(sender, bracket) {
 				return Err(Error::<T>::AlreadyQueued)?
@@ -331,13 +746,21 @@ pub mod pallet {
 			Ok(())
 		}
 
-		/// Empty all brackets, this is a founder only extrinsic.
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,1))]
-		pub fn empty_queue(origin: OriginFor<T>) -> DispatchResult {
+		/// Leave the queue.
+		#[pallet::weight(T::WeightInfo::dequeue())]
+		pub fn dequeue(origin: OriginFor<T>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
-			// Make sure sender is founder.
-			ensure!(sender == Self::founder_key().unwrap(), Error::<T>::OnlyFounderAllowed);
+			// Remove player from queue.
+			ensure!(T::MatchMaker::remove_queue(sender), Error::<T>::NotQueued);
+
+			Ok(())
+		}
+
+		/// Empty all brackets, this is an admin only extrinsic.
+		#[pallet::weight(T::WeightInfo::empty_queue())]
+		pub fn empty_queue(origin: OriginFor<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
 
 			// Empty queues
 			T::MatchMaker::all_empty_queue();
@@ -345,10 +768,13 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		#[pallet::weight(T::WeightInfo::initiate())]
 		pub fn initiate(origin: OriginFor<T>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
+			// Make sure play isn't paused.
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
 			// Make sure player has a running game.
 			ensure!(PlayerGame::<T>::contains_key(&sender), Error::<T>::GameDoesntExist);
 			let game_id = Self::player_game(&sender);
@@ -357,15 +783,32 @@ pub mod pallet {
 			ensure!(Games::<T>::contains_key(&game_id), Error::<T>::GameDoesntExist);
 
 			// get players game
-			let game = Self::games(&game_id);
+			let mut game = Self::games(&game_id);
 
-			// check if we have correct state
-			if let MatchState::Initiate(_) = game.match_state {
-				// check we have the correct state
+			// check if we have correct state and player didn't initiate yet
+			if let MatchState::Initiate(players) = &game.match_state {
+				ensure!(players.contains(&sender), Error::<T>::BadBehaviour);
 			} else {
 				Err(Error::<T>::BadBehaviour)?
 			}
 
+			// reserve the stake of players joining a wagered game and the participation bond
+			let stake = if game.staked.contains(&sender) { Zero::zero() } else { game.stake };
+			let bond = if game.bonded.contains(&sender) { Zero::zero() } else { game.bond };
+			ensure!(T::Currency::can_reserve(&sender, stake.saturating_add(bond)), Error::<T>::InsufficientBalance);
+			if !stake.is_zero() {
+				game.staked.try_push(sender.clone()).map_err(|_| Error::<T>::TooManyPlayers)?;
+				T::Currency::reserve(&sender, stake).map_err(|_| Error::<T>::InsufficientBalance)?;
+				Self::deposit_event(Event::StakeReserved(game_id, sender.clone(), stake));
+			}
+			if !bond.is_zero() {
+				game.bonded.try_push(sender.clone()).map_err(|_| Error::<T>::TooManyPlayers)?;
+				T::Currency::reserve(&sender, bond).map_err(|_| Error::<T>::InsufficientBalance)?;
+				Self::deposit_event(Event::BondReserved(game_id, sender.clone(), bond));
+			}
+
+			Self::deposit_event(Event::PlayerInitiated(game_id, sender.clone()));
+
 			// match state change
 			if !Self::match_state_change(sender, game) {
 				Err(Error::<T>::BadBehaviour)?
@@ -374,10 +817,15 @@ pub mod pallet {
 			Ok(())		
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn choose(origin: OriginFor<T>, choice: WeaponType, salt: [u8; 32]) -> DispatchResult {
+		/// Commit to a weapon, `choice_hash` is computed off-chain (see `Pallet::commitment`),
+		/// the salt stays secret until the reveal step.
+		#[pallet::weight(T::WeightInfo::choose())]
+		pub fn choose(origin: OriginFor<T>, choice_hash: T::Hash) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
+			// Make sure play isn't paused.
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
 			// Make sure player has a running game.
 			ensure!(PlayerGame::<T>::contains_key(&sender), Error::<T>::GameDoesntExist);
 			let game_id = Self::player_game(&sender);
@@ -390,15 +838,16 @@ pub mod pallet {
 			// get players game
 			let game = Self::games(&game_id);
 
-			// check if we have correct state
-			if let MatchState::Choose(_) = game.match_state {
-				// check we have the correct state
+			// check if we have correct state and player is still in the game
+			if let MatchState::Choose(players) = &game.match_state {
+				ensure!(players.contains(&sender), Error::<T>::BadBehaviour);
 			} else {
 				Err(Error::<T>::BadBehaviour)?
 			}
 
 			// insert choice into the double map.
-			<PlayerChoice<T>>::insert(game_id, &sender, Choice::Choose(Self::hash_choice(salt, choice as u8)));
+			<PlayerChoice<T>>::insert(game_id, &sender, Choice::Choose(choice_hash));
+			Self::deposit_event(Event::PlayerCommitted(game_id, sender.clone(), choice_hash));
 
 			// match state change
 			if !Self::match_state_change(sender, game) {
@@ -408,10 +857,17 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn reveal(origin: OriginFor<T>, choice: WeaponType, salt: [u8; 32]) -> DispatchResult {
+		/// Reveal the weapon and salt used for the commitment in the choose step.
+		#[pallet::weight(T::WeightInfo::reveal(T::MaxPlayers::get()))]
+		pub fn reveal(origin: OriginFor<T>, choice: WeaponOf<T>, salt: [u8; 32]) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
+			// Make sure play isn't paused.
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
+			// Make sure the weapon exists in the rule set.
+			ensure!(T::RuleSet::is_valid(&choice), Error::<T>::InvalidWeapon);
+
 			// Make sure player has a running game.
 			ensure!(PlayerGame::<T>::contains_key(&sender), Error::<T>::GameDoesntExist);
 			let game_id = Self::player_game(&sender);
@@ -427,9 +883,9 @@ pub mod pallet {
 			// get players game
 			let game = Self::games(&game_id);
 
-			// check if we have correct state
-			if let MatchState::Reveal(_) = game.match_state {
-				// check we have the correct state
+			// check if we have correct state and player didn't reveal yet
+			if let MatchState::Reveal(players) = &game.match_state {
+				ensure!(players.contains(&sender), Error::<T>::BadBehaviour);
 			} else {
 				Err(Error::<T>::BadBehaviour)?
 			}
@@ -437,8 +893,9 @@ pub mod pallet {
 			match player_choice {
 				Choice::Choose(org_hash) => {
 					// compare persisted hash with revealing value
-					if org_hash == Self::hash_choice(salt, choice.clone() as u8)  {
-						PlayerChoice::<T>::insert(&game_id, &sender, Choice::Reveal(choice));
+					if org_hash == Self::hash_choice(game_id, game.round, &sender, salt, &choice)  {
+						PlayerChoice::<T>::insert(&game_id, &sender, Choice::Reveal(choice.clone()));
+						Self::deposit_event(Event::PlayerRevealed(game_id, sender.clone(), choice));
 					} else {
 						Err(Error::<T>::BadBehaviour)?
 					}
@@ -453,6 +910,83 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Pause or resume play, this is an admin only extrinsic. Running games keep their state
+		/// while paused, they get a full phase to act once resumed. Resuming reschedules the
+		/// timeouts of every running game, `running_games` is a witness of their number.
+		#[pallet::weight(T::WeightInfo::set_paused(*running_games))]
+		pub fn set_paused(origin: OriginFor<T>, paused: bool, running_games: u32) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			if paused == Self::paused() {
+				return Ok(());
+			}
+			ensure!(paused || running_games >= Self::running_games(), Error::<T>::InvalidWitness);
+
+			Paused::<T>::put(paused);
+			if paused {
+				Self::deposit_event(Event::Paused);
+			} else {
+				Self::resume_games();
+				Self::deposit_event(Event::Resumed);
+			}
+
+			Ok(())
+		}
+
+		/// End a running game without a winner, stakes and bonds are returned to the players.
+		/// This is an admin only extrinsic, it works while play is paused.
+		#[pallet::weight(T::WeightInfo::abort_game(T::MaxPlayers::get()))]
+		pub fn abort_game(origin: OriginFor<T>, game_id: T::Hash) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			// Make sure game exists.
+			ensure!(Games::<T>::contains_key(&game_id), Error::<T>::GameDoesntExist);
+
+			// get game
+			let mut game = Self::games(&game_id);
+
+			// Make sure game is still running.
+			let from = game.match_state.phase();
+			ensure!(
+				matches!(from, Phase::Initiate | Phase::Choose | Phase::Reveal),
+				Error::<T>::NoRunningPhase
+			);
+
+			game.match_state = MatchState::Finished(GameResult::Abandoned);
+			Self::deposit_event(Event::PhaseAdvanced(game_id, from, Phase::Finished));
+
+			// get current blocknumber
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			game.last_action = block_number;
+			Games::<T>::insert(game_id, game.clone());
+
+			Self::finish_game(&game, GameResult::Abandoned, false);
+
+			Ok(())
+		}
+
+		/// End the current phase of a game, dispatched by the scheduler once the phase timed out.
+		#[pallet::weight(T::WeightInfo::force_timeout(T::MaxPlayers::get()))]
+		pub fn force_timeout(origin: OriginFor<T>, game_id: T::Hash) -> DispatchResult {
+			ensure_root(origin)?;
+
+			// Timeouts are frozen while paused, they get scheduled again on resume.
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
+			// Make sure game exists.
+			ensure!(Games::<T>::contains_key(&game_id), Error::<T>::GameDoesntExist);
+
+			// get game
+			let game = Self::games(&game_id);
+
+			// resolve the game with the players which acted in time
+			if !Self::timeout(game) {
+				Err(Error::<T>::NoRunningPhase)?
+			}
+
+			Ok(())
+		}
 	}
 }
 
@@ -477,8 +1011,37 @@ impl<T: Config> Pallet<T> {
 		return (seed, &sender, Self::encode_and_update_nonce()).using_encoded(T::Hashing::hash);
 	}
 
+	/// Checks if two players are allowed to start a game against each other.
+	fn ensure_can_play(
+		sender: &T::AccountId,
+		opponent: &T::AccountId
+	) -> DispatchResult {
+
+		// Don't allow playing against yourself.
+		ensure!(sender != opponent, Error::<T>::NoFakePlay);
+
+		// Don't allow queued player to create a game.
+		ensure!(!T::MatchMaker::is_queued(sender.clone()), Error::<T>::AlreadyQueued);
+		ensure!(!T::MatchMaker::is_queued(opponent.clone()), Error::<T>::AlreadyQueued);
+
+		// Make sure players have no board open.
+		ensure!(!PlayerGame::<T>::contains_key(sender), Error::<T>::PlayerHasGame);
+		ensure!(!PlayerGame::<T>::contains_key(opponent), Error::<T>::PlayerHasGame);
+
+		Ok(())
+	}
+
+	/// Bounds a part of the players of a game, like the players who still have to act.
+	fn bounded<Item>(
+		items: Vec<Item>
+	) -> BoundedVec<Item, T::MaxPlayers> {
+		BoundedVec::try_from(items).expect("game players are bounded already; qed")
+	}
+
 	fn create_game(
-		players: Vec<T::AccountId>
+		players: PlayersOf<T>,
+		best_of: u8,
+		stake: BalanceOf<T>
 	) -> T::Hash {
 
 		// get a random hash as board id
@@ -491,38 +1054,93 @@ impl<T: Config> Pallet<T> {
 		let game = Game {
 			id: game_id,
 			players: players.clone(),
+			stake: stake,
+			// stake of the creator is reserved already
+			staked: if stake.is_zero() { Default::default() } else { Self::bounded(sp_std::vec![players[0].clone()]) },
+			bond: T::ParticipationBond::get(),
+			bonded: Default::default(),
+			round: 0,
+			best_of: best_of,
+			scores: Self::bounded(players.iter().map(|player| (player.clone(), 0)).collect()),
+			redraws: 0,
 			last_action: block_number,
 			match_state: MatchState::Initiate(players.clone()),
 		};
 
 		// insert the new board into the storage
 		<Games<T>>::insert(game_id, game);
+		RunningGames::<T>::mutate(|count| *count = count.saturating_add(1));
+
+		// players have to initiate the game in time
+		Self::schedule_timeout(game_id, T::InitiateTimeout::get());
 
 		// insert conenction for each player with the game
-		for player in &players {
+		for player in players.iter() {
 			<PlayerGame<T>>::insert(player, game_id);
 		}
 		
 		// emit event for a new game creation
-		Self::deposit_event(Event::NewGame(game_id));
+		Self::deposit_event(Event::GameCreated(game_id, players.into_inner(), best_of, stake));
 
 		game_id
 	}
 
+	/// Game the account is playing, used by the runtime api.
+	pub fn game_of(
+		account: T::AccountId
+	) -> Option<T::Hash> {
+		PlayerGame::<T>::try_get(account).ok()
+	}
+
+	/// Game with the id, if it exists, used by the runtime api.
+	pub fn game(
+		game_id: T::Hash
+	) -> Option<GameOf<T>> {
+		Games::<T>::try_get(game_id).ok()
+	}
+
+	/// Players who still have to act in the current phase of a game, used by the runtime api.
+	pub fn pending_players(
+		game_id: T::Hash
+	) -> Vec<T::AccountId> {
+		match Self::game(game_id).map(|game| game.match_state) {
+			Some(MatchState::Initiate(players)) |
+			Some(MatchState::Choose(players)) |
+			Some(MatchState::Reveal(players)) => players.into_inner(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Computes the commitment `player` has to submit with `choose` in `round` of game `game_id`,
+	/// clients should use this to stay in line with the hashing scheme of the pallet.
+	#[cfg(feature = "std")]
+	pub fn commitment(
+		game_id: T::Hash,
+		round: u32,
+		player: &T::AccountId,
+		choice: WeaponOf<T>,
+		salt: [u8; 32]
+	) -> T::Hash {
+		Self::hash_choice(game_id, round, player, salt, &choice)
+	}
+
+	/// Hashes a choice, the commitment is bound to the game, round and player so it can't
+	/// be replayed by an opponent or in another game or round. Salt and choice are encoded
+	/// as separate fields so every byte of the salt counts.
 	fn hash_choice(
+		game_id: T::Hash,
+		round: u32,
+		player: &T::AccountId,
 		salt: [u8; 32],
-		choice: u8
+		choice: &WeaponOf<T>
 	) -> T::Hash {
-		let mut choice_value = salt;
-		choice_value[31] = choice as u8;
-		let choice_hashed = blake2_256(&choice_value);
 		// return hashed choice
-		choice_hashed.using_encoded(T::Hashing::hash)
+		(COMMITMENT_VERSION, game_id, round, player, salt, choice).using_encoded(T::Hashing::hash)
 	}
 
 	fn try_remove(
 		player: T::AccountId,
-		players: &mut Vec<T::AccountId>
+		players: &mut PlayersOf<T>
 	) -> bool {
 		if let Some(p) = players.iter().position(|x| *x == player) {
 			// remove player from vec
@@ -535,9 +1153,11 @@ impl<T: Config> Pallet<T> {
 
 	fn match_state_change(
 		player: T::AccountId,
-		mut game: Game<T::Hash, T::AccountId, T::BlockNumber>
+		mut game: GameOf<T>
 	) -> bool {
 
+		let from = game.match_state.phase();
+
 		match game.match_state.clone() {
 
 			MatchState::Initiate(mut players) => {
@@ -547,6 +1167,7 @@ impl<T: Config> Pallet<T> {
 				// check if all players have initiated
 				if players.is_empty() {
 					game.match_state = MatchState::Choose(game.players.clone());
+					Self::schedule_timeout(game.id, T::ChooseTimeout::get());
 				} else {
 					game.match_state = MatchState::Initiate(players);
 				}				
@@ -558,7 +1179,8 @@ impl<T: Config> Pallet<T> {
 				}
 				// check if all players have choosen
 				if players.is_empty() {
-					game.match_state = MatchState::Reveal(game.players.clone());
+					game.match_state = MatchState::Reveal(Self::bounded(Self::committed_players(&game)));
+					Self::schedule_timeout(game.id, T::RevealTimeout::get());
 				} else {
 					game.match_state = MatchState::Choose(players);
 				}
@@ -571,90 +1193,454 @@ impl<T: Config> Pallet<T> {
 				// check if all players have revealed
 				if players.is_empty() {
 					// do game evaluation here
-					game.match_state = MatchState::Finished(Self::evaluate(game.clone()));
+					game.match_state = Self::resolve_round(&mut game);
 				} else {
 					game.match_state = MatchState::Reveal(players);
 				}
 			},
 			_ => return false,
 		}
+
+		let to = game.match_state.phase();
+		if from != to {
+			Self::deposit_event(Event::PhaseAdvanced(game.id, from, to));
+		}
 		
 		// get current blocknumber
 		let block_number = <frame_system::Pallet<T>>::block_number();
 		game.last_action = block_number;
-		Games::<T>::insert(game.id, game);
-		
+		Games::<T>::insert(game.id, game.clone());
+
+		if let MatchState::Finished(result) = game.match_state.clone() {
+			Self::finish_game(&game, result, true);
+		}
+
 		true
 	}
 
-	fn evaluate(
-		game: Game<T::Hash, T::AccountId, T::BlockNumber>
-	) -> T::AccountId {
-
-		let mut last_choice: WeaponType = Default::default();
-		let mut last_player: T::AccountId = Default::default();
-		for player in &game.players {
-			if PlayerChoice::<T>::contains_key(game.id, player) {
-				if let Choice::Reveal(choice) = Self::player_choice(game.id, player) {
-					match Self::game_logic(&choice, &last_choice) {
-						1 => {
-							last_choice = choice.clone();
-							last_player = player.clone();
-						},
-						2 => {},
-						_ => {
-							last_player = Default::default();
-						}
-					}
-				}
+	/// Resolves a game whose current phase timed out, a single player who acted in time
+	/// wins by forfeit, revealed choices get evaluated, players who committed in time go on
+	/// to reveal, otherwise the game is abandoned.
+	fn timeout(
+		mut game: GameOf<T>
+	) -> bool {
 
-			}
-			
+		let (acted, late): (Vec<T::AccountId>, Vec<T::AccountId>) = match game.match_state.clone() {
+			MatchState::Initiate(pending) => (
+				game.players.iter()
+					.filter(|player| !pending.contains(player))
+					.cloned()
+					.collect(),
+				// only players of a series hold a bond from an earlier round, the others
+				// didn't reserve one yet
+				pending.into_inner(),
+			),
+			MatchState::Choose(pending) => (Self::committed_players(&game), pending.into_inner()),
+			MatchState::Reveal(pending) => (
+				Self::revealed_choices(&game).into_iter()
+					.map(|(player, _)| player)
+					.collect(),
+				pending.into_inner(),
+			),
+			_ => return false,
+		};
+		let choosing = matches!(game.match_state, MatchState::Choose(_));
+		let revealing = matches!(game.match_state, MatchState::Reveal(_));
+		// games some players never initiated aren't rated, players of a series initiated its
+		// first round already
+		let initiated = game.round > 0 || !matches!(game.match_state, MatchState::Initiate(_));
+		let from = game.match_state.phase();
+
+		let slashed = Self::slash_bonds(&mut game, &late);
+
+		game.match_state = match acted.len() {
+			0 => MatchState::Finished(GameResult::Abandoned),
+			1 => MatchState::Finished(GameResult::Forfeit(acted[0].clone())),
+			// only revealed choices are taken into account
+			_ if revealing => Self::resolve_round(&mut game),
+			// players who committed in time reveal, the late ones lost the round
+			_ if choosing => {
+				Self::schedule_timeout(game.id, T::RevealTimeout::get());
+				MatchState::Reveal(Self::bounded(acted.clone()))
+			},
+			_ => MatchState::Finished(GameResult::Abandoned),
+		};
+
+		// slashed bonds go to the winner, or get shared by the players who acted in time
+		// while the game goes on
+		match &game.match_state {
+			MatchState::Finished(GameResult::Winner(winner)) |
+			MatchState::Finished(GameResult::Forfeit(winner)) => T::Currency::resolve_creating(winner, slashed),
+			MatchState::Finished(_) => T::Slash::on_unbalanced(slashed),
+			_ => Self::share_slashed(slashed, &acted),
+		}
+
+		Self::deposit_event(Event::PhaseAdvanced(game.id, from, game.match_state.phase()));
+
+		// get current blocknumber
+		let block_number = <frame_system::Pallet<T>>::block_number();
+		game.last_action = block_number;
+		Games::<T>::insert(game.id, game.clone());
+
+		if let MatchState::Finished(result) = game.match_state.clone() {
+			Self::finish_game(&game, result, initiated);
 		}
 
-		last_player
+		true
 	}
 
-	fn game_logic(
-		a: &WeaponType,
-		b: &WeaponType
-	) -> u8 {
-		match a {
-			WeaponType::None => {
-				if a == b {
-					return 0;
-				} else {
-					return 2;
+	/// Players of the current round who committed their choice.
+	fn committed_players(
+		game: &GameOf<T>
+	) -> Vec<T::AccountId> {
+		game.players.iter()
+			.filter(|player| PlayerChoice::<T>::contains_key(game.id, *player))
+			.cloned()
+			.collect()
+	}
+
+	/// Revealed choices of the current round, in order of the game players.
+	fn revealed_choices(
+		game: &GameOf<T>
+	) -> Vec<(T::AccountId, WeaponOf<T>)> {
+		game.players.iter()
+			.filter_map(|player| match Self::player_choice(game.id, player) {
+				Choice::Reveal(choice) => Some((player.clone(), choice)),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Evaluates the revealed choices of a round, players who weren't beaten play another
+	/// round until a single one is left. Series continue until a player won the majority
+	/// of the rounds, draws of a series get replayed. Draws of single games get replayed
+	/// if `RematchOnDraw` is set, till `MaxRedraws` is reached.
+	fn resolve_round(
+		game: &mut GameOf<T>
+	) -> MatchStateOf<T> {
+		let choices = Self::revealed_choices(game);
+		match Self::evaluate(&choices) {
+			RoundOutcome::Winner(player) => {
+				let mut wins = 0;
+				let mut scores = game.scores.clone().into_inner();
+				if let Some((_, score)) = scores.iter_mut().find(|(p, _)| *p == player) {
+					*score = score.saturating_add(1);
+					wins = *score;
 				}
-			},
-			WeaponType::Rock => {
-				if a == b {
-					return 0; 
-				} else if let WeaponType::Paper = b {
-					return 2;
+				game.scores = Self::bounded(scores);
+				Self::deposit_event(Event::RoundWon(game.id, game.round, player.clone()));
+
+				if wins as u16 * 2 > game.best_of as u16 {
+					MatchState::Finished(GameResult::Winner(player))
 				} else {
-					return 1;
+					Self::next_round(game)
 				}
 			},
-			WeaponType::Paper => {
-				if a == b {
-					return 0; 
-				} else if let WeaponType::Scissor = b {
-					return 2;
-				} else {
-					return 1;
+			RoundOutcome::Draw if game.best_of > 1 => {
+				Self::deposit_event(Event::RoundDrawn(game.id, game.round));
+				Self::next_round(game)
+			},
+			RoundOutcome::Draw if T::RematchOnDraw::get() && game.redraws < T::MaxRedraws::get() => {
+				game.redraws = game.redraws.saturating_add(1);
+				Self::deposit_event(Event::RoundDrawn(game.id, game.round));
+				Self::reset_choices(game);
+				Self::schedule_timeout(game.id, T::ChooseTimeout::get());
+				// players of the drawn round choose again
+				MatchState::Choose(Self::bounded(choices.into_iter().map(|(player, _)| player).collect()))
+			},
+			RoundOutcome::Draw => MatchState::Finished(GameResult::Draw),
+			RoundOutcome::Survivors(players) => {
+				Self::reset_choices(game);
+				Self::schedule_timeout(game.id, T::ChooseTimeout::get());
+				MatchState::Choose(Self::bounded(players))
+			},
+		}
+	}
+
+	/// Starts the next round of a series, all players have to initiate again.
+	fn next_round(
+		game: &mut GameOf<T>
+	) -> MatchStateOf<T> {
+		Self::reset_choices(game);
+		Self::schedule_timeout(game.id, T::InitiateTimeout::get());
+		MatchState::Initiate(game.players.clone())
+	}
+
+	/// Clears the choices of the last round, commitments of the next round are bound to
+	/// the new round number.
+	fn reset_choices(
+		game: &mut GameOf<T>
+	) {
+		PlayerChoice::<T>::remove_prefix(&game.id);
+		game.round = game.round.saturating_add(1);
+	}
+
+	/// Stops the timeouts of a finished game and removes it once the cleanup delay passed, the
+	/// ratings of the players are only updated for `rated` games.
+	fn finish_game(
+		game: &GameOf<T>,
+		result: GameResult<T::AccountId>,
+		rated: bool
+	) {
+		let game_id = game.id;
+		Self::cancel_timeout(game_id);
+		RunningGames::<T>::mutate(|count| *count = count.saturating_sub(1));
+
+		Self::settle_stakes(game, &result);
+		Self::release_bonds(game);
+		if rated {
+			Self::update_ratings(game, &result);
+		}
+
+		Self::deposit_event(Event::GameFinished(game_id, result));
+
+		let delay = T::CleanupDelay::get();
+		if delay.is_zero() {
+			Self::remove_game(game_id);
+		} else {
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			Self::schedule_cleanup(game_id, block_number + delay);
+		}
+	}
+
+	/// Removes a finished game at block `when`, or the first block after it with room left.
+	fn schedule_cleanup(
+		game_id: T::Hash,
+		when: T::BlockNumber
+	) {
+		let mut when = when;
+		while GameCleanup::<T>::try_mutate(when, |games| games.try_push(game_id)).is_err() {
+			when = when.saturating_add(1u32.into());
+		}
+	}
+
+	/// Pays the reserved stakes to the winner of a game, or returns them if there is none.
+	fn settle_stakes(
+		game: &GameOf<T>,
+		result: &GameResult<T::AccountId>
+	) {
+		match result {
+			GameResult::Winner(winner) | GameResult::Forfeit(winner) => Self::pay_winner(game, winner),
+			GameResult::Draw | GameResult::Abandoned => Self::release_stakes(game),
+		}
+	}
+
+	/// Returns the reserved stakes of a game in full.
+	fn release_stakes(
+		game: &GameOf<T>
+	) {
+		for player in game.staked.iter() {
+			let remaining = T::Currency::unreserve(player, game.stake);
+			Self::deposit_event(Event::StakeReleased(game.id, player.clone(), game.stake - remaining));
+		}
+	}
+
+	/// Pays the pot of a wagered game to the winner, after the house fee is taken from it.
+	/// The fee is rounded down and taken from the stakes of the losers first. Without a stake
+	/// of a loser in the pot nothing got won, the stake of the winner is returned without a fee.
+	fn pay_winner(
+		game: &GameOf<T>,
+		winner: &T::AccountId
+	) {
+		if !game.staked.iter().any(|player| player != winner) {
+			Self::release_stakes(game);
+			return;
+		}
+
+		let pot = game.stake.saturating_mul((game.staked.len() as u32).into());
+		let mut fee_remaining = T::HouseFee::get().mul_floor(pot);
+		let mut fees = NegativeImbalanceOf::<T>::zero();
+
+		let losers = game.staked.iter().filter(|player| *player != winner);
+		let winners = game.staked.iter().filter(|player| *player == winner);
+		for player in losers.chain(winners) {
+			// take the fee out of the reserved stake
+			let cut = fee_remaining.min(game.stake);
+			if !cut.is_zero() {
+				let (imbalance, _) = T::Currency::slash_reserved(player, cut);
+				fee_remaining = fee_remaining.saturating_sub(imbalance.peek());
+				fees.subsume(imbalance);
+			}
+
+			let rest = game.stake - cut;
+			if player == winner {
+				let remaining = T::Currency::unreserve(player, rest);
+				Self::deposit_event(Event::StakeReleased(game.id, player.clone(), rest - remaining));
+			} else {
+				let remaining = T::Currency::repatriate_reserved(player, winner, rest, BalanceStatus::Free)
+					.unwrap_or(rest);
+				Self::deposit_event(Event::StakePaid(game.id, player.clone(), winner.clone(), rest - remaining));
+			}
+		}
+
+		let fee = fees.peek();
+		T::FeeDestination::on_unbalanced(fees);
+		Self::deposit_event(Event::GameSettled(game.id, winner.clone(), pot.saturating_sub(fee), fee));
+	}
+
+	/// Updates the ratings of the players of a finished game. The winner scores a win against
+	/// every other player, a draw counts as a draw between all players and abandoned games
+	/// aren't rated. Changes are based on the ratings before the game.
+	fn update_ratings(
+		game: &GameOf<T>,
+		result: &GameResult<T::AccountId>
+	) {
+		let k_factor = T::KFactor::get();
+		let ratings: Vec<Rating> = game.players.iter().map(|player| Self::player_rating(player)).collect();
+		let mut changes = sp_std::vec![0i64; ratings.len()];
+		let mut rate = |a: usize, b: usize, score: i64| {
+			let change = rating::rating_change(ratings[a], ratings[b], score, k_factor);
+			changes[a] += change;
+			changes[b] -= change;
+		};
+
+		match result {
+			GameResult::Winner(winner) | GameResult::Forfeit(winner) => {
+				let winner = match game.players.iter().position(|player| player == winner) {
+					Some(index) => index,
+					None => return,
+				};
+				for opponent in (0..ratings.len()).filter(|index| *index != winner) {
+					rate(winner, opponent, SCORE_ONE);
 				}
 			},
-			WeaponType::Scissor => {
-				if a == b {
-					return 0; 
-				} else if let WeaponType::Rock = b {
-					return 2;
-				} else {
-					return 1;
+			GameResult::Draw => {
+				for a in 0..ratings.len() {
+					for b in a + 1..ratings.len() {
+						rate(a, b, SCORE_DRAW);
+					}
 				}
 			},
+			GameResult::Abandoned => return,
+		}
+
+		for (index, player) in game.players.iter().enumerate() {
+			let rating = rating::apply_change(ratings[index], changes[index]);
+			PlayerRating::<T>::insert(player, rating);
+			Self::deposit_event(Event::RatingUpdated(player.clone(), ratings[index], rating));
 		}
 	}
-}
 
+	/// Slashes the participation bond of players who didn't act in time.
+	fn slash_bonds(
+		game: &mut GameOf<T>,
+		late: &[T::AccountId]
+	) -> NegativeImbalanceOf<T> {
+		let mut slashed = NegativeImbalanceOf::<T>::zero();
+		for player in late {
+			if let Some(index) = game.bonded.iter().position(|p| p == player) {
+				game.bonded.remove(index);
+				let (imbalance, _) = T::Currency::slash_reserved(player, game.bond);
+				Self::deposit_event(Event::BondSlashed(game.id, player.clone(), imbalance.peek()));
+				slashed.subsume(imbalance);
+			}
+		}
+		slashed
+	}
+
+	/// Shares slashed bonds equally between players, what can't be shared equally goes to
+	/// `T::Slash`.
+	fn share_slashed(
+		mut slashed: NegativeImbalanceOf<T>,
+		players: &[T::AccountId]
+	) {
+		if !players.is_empty() {
+			let share = slashed.peek() / (players.len() as u32).into();
+			for player in players {
+				let (part, rest) = slashed.split(share);
+				T::Currency::resolve_creating(player, part);
+				slashed = rest;
+			}
+		}
+		T::Slash::on_unbalanced(slashed);
+	}
+
+	/// Returns the participation bonds of a finished game.
+	fn release_bonds(
+		game: &GameOf<T>
+	) {
+		for player in game.bonded.iter() {
+			let remaining = T::Currency::unreserve(player, game.bond);
+			Self::deposit_event(Event::BondReleased(game.id, player.clone(), game.bond - remaining));
+		}
+	}
+
+	/// Removes a game with all its player entries.
+	fn remove_game(
+		game_id: T::Hash
+	) {
+		let game = Games::<T>::take(&game_id);
+
+		for player in game.players.iter() {
+			<PlayerGame<T>>::remove(player);
+		}
+		PlayerChoice::<T>::remove_prefix(&game_id);
+
+		Self::deposit_event(Event::GameRemoved(game_id));
+	}
+
+	/// Schedules the timeout of the current phase, replacing the one of the previous phase.
+	fn schedule_timeout(
+		game_id: T::Hash,
+		timeout: T::BlockNumber
+	) {
+		Self::cancel_timeout(game_id);
+
+		let when = <frame_system::Pallet<T>>::block_number() + timeout;
+		PhaseDeadline::<T>::insert(game_id, when);
+		if T::Scheduler::schedule_named(
+			Self::timeout_name(game_id, when),
+			DispatchTime::At(when),
+			None,
+			63,
+			frame_system::RawOrigin::Root.into(),
+			Call::<T>::force_timeout(game_id).into(),
+		).is_err() {
+			log::error!("rps: failed to schedule timeout of game {:?}", game_id);
+		}
+	}
+
+	/// Gives every running game a full phase from now on, players couldn't act while paused.
+	/// Running games are the ones with a deadline, finished games aren't visited.
+	fn resume_games() {
+		let block_number = <frame_system::Pallet<T>>::block_number();
+		let running = PhaseDeadline::<T>::iter_keys().collect::<Vec<_>>();
+		for game_id in running {
+			let mut game = Games::<T>::get(&game_id);
+			let timeout = match game.match_state {
+				MatchState::Initiate(_) => T::InitiateTimeout::get(),
+				MatchState::Choose(_) => T::ChooseTimeout::get(),
+				MatchState::Reveal(_) => T::RevealTimeout::get(),
+				_ => continue,
+			};
+			Self::schedule_timeout(game_id, timeout);
+			game.last_action = block_number;
+			Games::<T>::insert(game_id, game);
+		}
+	}
+
+	/// Cancels the pending timeout of a game, if there is any.
+	fn cancel_timeout(
+		game_id: T::Hash
+	) {
+		if let Some(when) = PhaseDeadline::<T>::take(game_id) {
+			let _ = T::Scheduler::cancel_named(Self::timeout_name(game_id, when));
+		}
+	}
+
+	/// Name of the scheduled timeout of a game. The scheduler forgets the name of the timeout it
+	/// dispatches, so the timeout of the next phase, scheduled during that dispatch, needs
+	/// another name to stay cancellable.
+	fn timeout_name(
+		game_id: T::Hash,
+		when: T::BlockNumber
+	) -> Vec<u8> {
+		(RPS_ID, game_id, when).encode()
+	}
+
+	fn evaluate(
+		choices: &[(T::AccountId, WeaponOf<T>)]
+	) -> RoundOutcome<T::AccountId> {
+		rules::eliminate(choices, |a, b| T::RuleSet::beats(a, b) == Ordering::Greater)
+	}
+}
@@ -0,0 +1,109 @@
+//! Matchmaking with players leaving the queue.
+//!
+//! `MatchFunc` can only empty whole brackets, `Dequeueing` adds the removal of single players to
+//! any `MatchFunc` matchmaker. Removed players stay in the queue of the matchmaker but are marked
+//! in `Dequeued`, once they get matched they are dropped and the rest of the match is queued again.
+//! The matchmaker can only add players at the end of a bracket, so the rest of the match loses
+//! its place in the queue.
+
+use pallet_matchmaker::MatchFunc;
+use sp_runtime::traits::Convert;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+use crate::{Config, Dequeued, Pallet};
+
+/// Matchmaking which lets single players leave the queue.
+pub trait MatchQueue<AccountId>: MatchFunc<AccountId> {
+	/// Removes a queued player, returns false if the player isn't queued.
+	fn remove_queue(account_id: AccountId) -> bool;
+
+	/// Tries to match players like `try_match`, also returns the number of players who left the
+	/// queue and got dropped on the way. Once `max_dropped` players got dropped no match is
+	/// returned, the match reaching the limit is dropped in full so it can be exceeded by less
+	/// than the players of a match.
+	fn try_match_dropping(max_dropped: u32) -> (Vec<AccountId>, u32);
+}
+
+/// Adds `MatchQueue` to the matchmaker `M`.
+pub struct Dequeueing<T, M>(PhantomData<(T, M)>);
+
+impl<T: Config, M: MatchFunc<T::AccountId>> MatchFunc<T::AccountId> for Dequeueing<T, M> {
+	fn empty_queue(bracket: u8) {
+		M::empty_queue(bracket);
+		let removed: Vec<T::AccountId> = Dequeued::<T>::iter()
+			.filter(|(_, b)| *b == bracket)
+			.map(|(player, _)| player)
+			.collect();
+		for player in removed {
+			Dequeued::<T>::remove(player);
+		}
+	}
+
+	fn all_empty_queue() {
+		M::all_empty_queue();
+		Dequeued::<T>::remove_all();
+	}
+
+	fn add_queue(account_id: T::AccountId, bracket: u8) -> bool {
+		// players who left are still in the queue of the matchmaker, they rejoin at their old place
+		// if it's in the same bracket
+		match Dequeued::<T>::get(&account_id) {
+			Some(b) if b == bracket => {
+				Dequeued::<T>::remove(&account_id);
+				true
+			},
+			Some(_) => false,
+			None => M::add_queue(account_id, bracket),
+		}
+	}
+
+	fn try_match() -> Vec<T::AccountId> {
+		Self::try_match_dropping(u32::MAX).0
+	}
+
+	fn is_queued(account_id: T::AccountId) -> bool {
+		!Dequeued::<T>::contains_key(&account_id) && M::is_queued(account_id)
+	}
+}
+
+impl<T: Config, M: MatchFunc<T::AccountId>> MatchQueue<T::AccountId> for Dequeueing<T, M> {
+	fn remove_queue(account_id: T::AccountId) -> bool {
+		if !Self::is_queued(account_id.clone()) {
+			return false;
+		}
+		// queued players can't start a game, so their rating didn't change since they got queued
+		// and this is the bracket of their entry
+		let bracket = T::BracketOf::convert(Pallet::<T>::player_rating(&account_id));
+		Dequeued::<T>::insert(account_id, bracket);
+		true
+	}
+
+	fn try_match_dropping(max_dropped: u32) -> (Vec<T::AccountId>, u32) {
+		let mut dropped: u32 = 0;
+		loop {
+			let players = M::try_match();
+			if !players.iter().any(|player| Dequeued::<T>::contains_key(player)) {
+				return (players, dropped);
+			}
+
+			// drop players who left the queue, the others are queued again at the end of the bracket
+			let mut bracket = 0;
+			let mut remaining = Vec::new();
+			for player in players {
+				match Dequeued::<T>::take(&player) {
+					Some(b) => {
+						bracket = b;
+						dropped = dropped.saturating_add(1);
+					},
+					None => remaining.push(player),
+				}
+			}
+			for player in remaining {
+				M::add_queue(player, bracket);
+			}
+			if dropped >= max_dropped {
+				return (Vec::new(), dropped);
+			}
+		}
+	}
+}
@@ -0,0 +1,91 @@
+//! Storage migrations of the rps pallet.
+//!
+//! Migrations are not run automatically, add the ones needed to the `Executive` of the
+//! runtime for the upgrade they belong to and remove them afterwards. Migrations of the
+//! storage layout check `StorageVersion` and only run on the version they migrate from.
+
+use super::*;
+
+use frame_support::{
+	migration::remove_storage_prefix,
+	traits::{Get, OnRuntimeUpgrade, PalletInfo},
+	weights::Weight,
+};
+use sp_std::marker::PhantomData;
+
+pub mod v3;
+pub use v3::MigrateToV3;
+
+/// Storage prefix of the pallet, for storage items which are not part of the pallet anymore.
+pub(crate) fn pallet_prefix<T: Config>() -> &'static [u8] {
+	<T as frame_system::Config>::PalletInfo::name::<Pallet<T>>()
+		.expect("pallet is part of the runtime; qed")
+		.as_bytes()
+}
+
+/// The founder key got replaced by `Config::AdminOrigin`, this removes the key stored
+/// by the genesis config of older versions.
+pub struct RemoveFounderKey<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for RemoveFounderKey<T> {
+	fn on_runtime_upgrade() -> Weight {
+		remove_storage_prefix(pallet_prefix::<T>(), b"FounderKey", &[]);
+
+		info!("rps: removed founder key");
+
+		T::DbWeight::get().writes(1)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		if frame_support::migration::have_storage_value(pallet_prefix::<T>(), b"FounderKey", &[]) {
+			return Err("founder key left in storage");
+		}
+		Ok(())
+	}
+}
+
+/// Removes the `Something` storage of the node template, migrates the storage from `V1` to `V2`.
+pub struct RemoveTemplateStorage<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for RemoveTemplateStorage<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if StorageVersion::<T>::get() != Releases::V1 {
+			info!("rps: template storage already removed, skipping migration");
+			return T::DbWeight::get().reads(1);
+		}
+
+		remove_storage_prefix(pallet_prefix::<T>(), b"Something", &[]);
+		StorageVersion::<T>::put(Releases::V2);
+
+		info!("rps: removed template storage, storage version V2");
+
+		T::DbWeight::get().reads_writes(1, 2)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		use frame_support::migration::{get_storage_value, have_storage_value};
+
+		if StorageVersion::<T>::get() != Releases::V1 {
+			return Ok(());
+		}
+		// the only value ever stored under the key was a `u32`
+		if have_storage_value(pallet_prefix::<T>(), b"Something", &[]) &&
+			get_storage_value::<u32>(pallet_prefix::<T>(), b"Something", &[]).is_none() {
+			return Err("unexpected value stored in Something");
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		if StorageVersion::<T>::get() < Releases::V2 {
+			return Err("storage version not updated");
+		}
+		if frame_support::migration::have_storage_value(pallet_prefix::<T>(), b"Something", &[]) {
+			return Err("template storage left");
+		}
+		Ok(())
+	}
+}
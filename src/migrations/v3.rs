@@ -0,0 +1,228 @@
+//! Migration of the game data from `V2` to `V3`.
+//!
+//! Games of the initial layout have no stakes, bonds, rounds or results, a finished game stored
+//! its winner or the default account on a draw. Their commitments used the initial hashing
+//! scheme and can't be revealed anymore. Games are translated with neutral values, players of
+//! games in the choose or reveal phase commit again, running games get the timeouts they were
+//! missing and finished games get removed after `CleanupDelay`.
+
+use crate::*;
+
+use frame_support::{traits::{Get, OnRuntimeUpgrade}, weights::Weight};
+use sp_std::{convert::TryFrom, fmt::Debug, marker::PhantomData};
+
+/// Match state of the `V2` layout.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub enum OldMatchState<AccountId> {
+	None,
+	Initiate(Vec<AccountId>),
+	Choose(Vec<AccountId>),
+	Reveal(Vec<AccountId>),
+	/// Winner of the game, the default account on a draw.
+	Finished(AccountId),
+}
+
+/// Weapons of the `V2` layout, they keep their index in the rule sets.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub enum OldWeaponType {
+	None,
+	Rock,
+	Paper,
+	Scissor,
+}
+
+/// Choice of the `V2` layout.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub enum OldChoice<Hash> {
+	None,
+	Choose(Hash),
+	Reveal(OldWeaponType),
+}
+
+/// Game of the `V2` layout.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct OldGame<Hash, AccountId, BlockNumber> {
+	pub id: Hash,
+	pub players: Vec<AccountId>,
+	pub last_action: BlockNumber,
+	pub match_state: OldMatchState<AccountId>,
+}
+
+/// Game of the `V2` layout as stored by the runtime.
+pub type OldGameOf<T> = OldGame<
+	<T as frame_system::Config>::Hash,
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+>;
+
+/// Translates a game of the `V2` layout, games waiting for commitments or reveals go back to
+/// the choose phase with all players. Games with more than `MaxPlayers` players can't be
+/// translated.
+pub fn translate_game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>(
+	old: OldGame<Hash, AccountId, BlockNumber>
+) -> Option<Game<Hash, AccountId, BlockNumber, Balance, MaxPlayers>>
+where
+	Hash: Clone + PartialEq + Debug,
+	AccountId: Clone + PartialEq + Debug + Default,
+	BlockNumber: Clone + PartialEq + Debug,
+	Balance: Clone + PartialEq + Debug + Zero,
+	MaxPlayers: Get<u32>,
+{
+	let players = BoundedVec::<AccountId, MaxPlayers>::try_from(old.players).ok()?;
+	let match_state = match old.match_state {
+		OldMatchState::None => MatchState::None,
+		// pending players are part of the players
+		OldMatchState::Initiate(pending) => MatchState::Initiate(BoundedVec::try_from(pending).ok()?),
+		OldMatchState::Choose(_) | OldMatchState::Reveal(_) => MatchState::Choose(players.clone()),
+		OldMatchState::Finished(winner) if winner == AccountId::default() => MatchState::Finished(GameResult::Draw),
+		OldMatchState::Finished(winner) => MatchState::Finished(GameResult::Winner(winner)),
+	};
+	let scores = players.iter().map(|player| (player.clone(), 0)).collect::<Vec<_>>();
+	Some(Game {
+		id: old.id,
+		scores: BoundedVec::try_from(scores).ok()?,
+		players,
+		stake: Zero::zero(),
+		staked: Default::default(),
+		bond: Zero::zero(),
+		bonded: Default::default(),
+		round: 0,
+		best_of: 1,
+		redraws: 0,
+		last_action: old.last_action,
+		match_state,
+	})
+}
+
+/// Translates a choice of the `V2` layout, commitments are dropped and revealed weapons the
+/// rule set doesn't know are dropped as well.
+pub fn translate_choice<Hash, Weapon: Decode>(old: OldChoice<Hash>) -> Option<Choice<Hash, Weapon>> {
+	match old {
+		OldChoice::None => Some(Choice::None),
+		OldChoice::Choose(_) => None,
+		OldChoice::Reveal(weapon) => Weapon::decode(&mut &weapon.encode()[..]).ok().map(Choice::Reveal),
+	}
+}
+
+/// Migrates `Games` and `PlayerChoice` from `V2` to `V3`, run it after `RemoveTemplateStorage`
+/// when upgrading from `V1`.
+pub struct MigrateToV3<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if StorageVersion::<T>::get() != Releases::V2 {
+			info!("rps: storage version is not V2, skipping migration to V3");
+			return T::DbWeight::get().reads(1);
+		}
+
+		let mut reads: Weight = 1;
+		let mut writes: Weight = 0;
+
+		PlayerChoice::<T>::translate::<OldChoice<T::Hash>, _>(|_, _, old| {
+			reads = reads.saturating_add(1);
+			writes = writes.saturating_add(1);
+			translate_choice::<_, WeaponOf<T>>(old)
+		});
+
+		let mut games: u32 = 0;
+		Games::<T>::translate::<OldGameOf<T>, _>(|game_id, old| {
+			games = games.saturating_add(1);
+			let players = old.players.clone();
+			let game = translate_game(old);
+			if game.is_none() {
+				// players of games which can't be translated are free again
+				log::error!("rps: game {:?} exceeds MaxPlayers, removing it", game_id);
+				for player in &players {
+					PlayerGame::<T>::remove(player);
+				}
+				PlayerChoice::<T>::remove_prefix(&game_id);
+				writes = writes.saturating_add(players.len() as Weight);
+			}
+			game
+		});
+		reads = reads.saturating_add(games as Weight);
+		writes = writes.saturating_add(games as Weight);
+
+		// legacy games had no timeouts and finished games were never removed
+		let block_number = <frame_system::Pallet<T>>::block_number();
+		let mut running: u32 = 0;
+		for (game_id, mut game) in Games::<T>::iter() {
+			reads = reads.saturating_add(1);
+			let timeout = match game.match_state {
+				MatchState::Initiate(_) => T::InitiateTimeout::get(),
+				MatchState::Choose(_) => {
+					// reveals of games sent back to the choose phase
+					PlayerChoice::<T>::remove_prefix(&game_id);
+					writes = writes.saturating_add(game.players.len() as Weight);
+					T::ChooseTimeout::get()
+				},
+				MatchState::Finished(_) => {
+					Pallet::<T>::schedule_cleanup(game_id, block_number + T::CleanupDelay::get());
+					writes = writes.saturating_add(1);
+					continue;
+				},
+				_ => continue,
+			};
+
+			// players get a full phase from now on
+			Pallet::<T>::schedule_timeout(game_id, timeout);
+			game.last_action = block_number;
+			Games::<T>::insert(&game_id, &game);
+			running = running.saturating_add(1);
+			writes = writes.saturating_add(3);
+		}
+		RunningGames::<T>::put(running);
+
+		StorageVersion::<T>::put(Releases::V3);
+		writes = writes.saturating_add(2);
+
+		info!("rps: migrated {} games, storage version V3", games);
+
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		use frame_support::migration::storage_iter;
+
+		if StorageVersion::<T>::get() != Releases::V2 {
+			return Ok(());
+		}
+		// `()` decodes from any value, so this counts every stored game
+		let stored = storage_iter::<()>(super::pallet_prefix::<T>(), b"Games").count();
+		let decodable = storage_iter::<OldGameOf<T>>(super::pallet_prefix::<T>(), b"Games").count();
+		if stored != decodable {
+			return Err("game not stored with the V2 layout");
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		use frame_support::migration::storage_iter;
+
+		if StorageVersion::<T>::get() < Releases::V3 {
+			return Err("storage version not updated");
+		}
+		let stored = storage_iter::<()>(super::pallet_prefix::<T>(), b"Games").count();
+		if stored != Games::<T>::iter().count() {
+			return Err("game not stored with the V3 layout");
+		}
+		let mut running: u32 = 0;
+		for (game_id, game) in Games::<T>::iter() {
+			match game.match_state {
+				MatchState::Initiate(_) | MatchState::Choose(_) | MatchState::Reveal(_) => {
+					if PhaseDeadline::<T>::get(&game_id).is_none() {
+						return Err("running game without timeout");
+					}
+					running += 1;
+				},
+				_ => {},
+			}
+		}
+		if running != RunningGames::<T>::get() {
+			return Err("running games not counted");
+		}
+		Ok(())
+	}
+}
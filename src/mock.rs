@@ -1,22 +1,25 @@
 use super::*;
 use crate as pallet_rps;
+use crate::rating::RatingBrackets;
+use crate::matchmaking::Dequeueing;
 
 use sp_core::H256;
 
 use frame_support::{
 	parameter_types,
-	traits::{OnInitialize, OnFinalize},
-//	weights::Weight,
+	traits::{OnInitialize, OnFinalize, Get, Currency, OnUnbalanced},
+	weights::Weight,
 };
 
 use frame_support_test::TestRandomness;
+use std::cell::RefCell;
 use sp_runtime::{
 	BuildStorage,
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
-//	Perbill,
+	Perbill,
 };
-//use frame_system::{EnsureRoot};
+use frame_system::{EnsureRoot};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -29,8 +32,10 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
 		MatchMaker: pallet_matchmaker::{Pallet, Call, Storage, Event<T>},
-		RockPaperScissor: pallet_rps::{Pallet, Call, Config<T>, Storage, Event<T>},
+		RockPaperScissor: pallet_rps::{Pallet, Call, Config, Storage, Event<T>},
 	}
 );
 
@@ -57,7 +62,7 @@ impl frame_system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -65,6 +70,36 @@ impl frame_system::Config for Test {
 	type OnSetCode = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MaximumSchedulerWeight: Weight = 1_000_000_000;
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRoot<u64>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const AmountPlayers: u8 = 2;
 	pub const AmountBrackets: u8 = 2;
@@ -77,10 +112,90 @@ impl pallet_matchmaker::Config for Test {
 	type AmountBrackets = AmountBrackets;
 }
 
+parameter_types! {
+	pub const InitiateTimeout: u64 = 10;
+	pub const ChooseTimeout: u64 = 10;
+	pub const RevealTimeout: u64 = 10;
+	pub const ParticipationBond: u64 = 10;
+	pub const MaxRedraws: u8 = 2;
+	pub const CleanupDelay: u64 = 5;
+	pub const MaxPlayers: u32 = 4;
+	pub const MaxGamesPerBlock: u32 = 10;
+	pub const MaxDroppedPerBlock: u32 = 2;
+	pub const KFactor: u32 = 32;
+	pub const StartingRating: u32 = 1500;
+	pub const BracketWidth: u32 = 1600;
+}
+
+thread_local! {
+	static REMATCH_ON_DRAW: RefCell<bool> = RefCell::new(false);
+	static HOUSE_FEE: RefCell<Perbill> = RefCell::new(Perbill::from_parts(0));
+}
+
+/// Rematch mode of the test runtime, off by default.
+pub struct RematchOnDraw;
+impl Get<bool> for RematchOnDraw {
+	fn get() -> bool {
+		REMATCH_ON_DRAW.with(|v| *v.borrow())
+	}
+}
+impl RematchOnDraw {
+	pub fn set(rematch: bool) {
+		REMATCH_ON_DRAW.with(|v| *v.borrow_mut() = rematch);
+	}
+}
+
+/// House fee of the test runtime, zero by default.
+pub struct HouseFee;
+impl Get<Perbill> for HouseFee {
+	fn get() -> Perbill {
+		HOUSE_FEE.with(|v| *v.borrow())
+	}
+}
+impl HouseFee {
+	pub fn set(fee: Perbill) {
+		HOUSE_FEE.with(|v| *v.borrow_mut() = fee);
+	}
+}
+
+/// Account receiving the house fee.
+pub const HOUSE: u64 = 100;
+
+/// Pays the house fee to the house account.
+pub struct House;
+impl OnUnbalanced<pallet_balances::NegativeImbalance<Test>> for House {
+	fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Test>) {
+		Balances::resolve_creating(&HOUSE, amount);
+	}
+}
+
 impl pallet_rps::Config for Test {
 	type Event = Event;
 	type Randomness = TestRandomness<Self>;
-	type MatchMaker = MatchMaker;
+	type MatchMaker = Dequeueing<Test, MatchMaker>;
+	type AdminOrigin = EnsureRoot<u64>;
+	type RuleSet = rules::ClassicRules;
+	type Currency = Balances;
+	type ParticipationBond = ParticipationBond;
+	type Slash = ();
+	type HouseFee = HouseFee;
+	type FeeDestination = House;
+	type Proposal = Call;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type InitiateTimeout = InitiateTimeout;
+	type ChooseTimeout = ChooseTimeout;
+	type RevealTimeout = RevealTimeout;
+	type RematchOnDraw = RematchOnDraw;
+	type MaxRedraws = MaxRedraws;
+	type CleanupDelay = CleanupDelay;
+	type MaxPlayers = MaxPlayers;
+	type MaxGamesPerBlock = MaxGamesPerBlock;
+	type MaxDroppedPerBlock = MaxDroppedPerBlock;
+	type KFactor = KFactor;
+	type StartingRating = StartingRating;
+	type BracketOf = RatingBrackets<BracketWidth, AmountBrackets>;
+	type WeightInfo = ();
 }
 
 /// Build genesis storage according to the mock runtime.
@@ -88,6 +203,10 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	//frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
 	let t = GenesisConfig {
 			frame_system: Default::default(),
+			pallet_balances: pallet_balances::GenesisConfig {
+				// account 9 can't afford much
+				balances: (0..9).map(|account| (account, 1_000)).chain(Some((9, 10))).collect(),
+			},
 			pallet_rps: Default::default(),
 		}.build_storage().unwrap();
 		t.into()
@@ -104,7 +223,7 @@ pub fn run_to_block(n: u64) {
 		if System::block_number() > 1 {
 			// mock on_finalize
 			System::on_finalize(System::block_number());
-			//Scheduler::on_finalize(System::block_number());
+			Scheduler::on_finalize(System::block_number());
 			RockPaperScissor::on_finalize(System::block_number());
 		}
 
@@ -112,7 +231,7 @@ pub fn run_to_block(n: u64) {
 		
 		// mock on_initialize
 		System::on_initialize(System::block_number());
-		//Scheduler::on_initialize(System::block_number());
+		Scheduler::on_initialize(System::block_number());
 		RockPaperScissor::on_initialize(System::block_number());
 	}
 }
@@ -0,0 +1,81 @@
+//! Elo rating of players in fixed-point arithmetic.
+//!
+//! Scores are in parts per million, a win is `SCORE_ONE`, a draw half of it. The expected score
+//! is looked up in a table of the logistic curve `1 / (1 + 10^(-d / 400))` in steps of 25 rating
+//! points and interpolated linearly in between, rating differences beyond 800 count as 800.
+
+use frame_support::traits::Get;
+use sp_runtime::traits::Convert;
+use sp_std::marker::PhantomData;
+
+/// Rating of a player.
+pub type Rating = u32;
+
+/// Score of a won game, in parts per million.
+pub const SCORE_ONE: i64 = 1_000_000;
+
+/// Score of a drawn game, in parts per million.
+pub const SCORE_DRAW: i64 = SCORE_ONE / 2;
+
+/// Rating points between two entries of `EXPECTED`.
+const STEP: u32 = 25;
+
+/// Expected score of the better rated player, for differences of 0, 25, .. 800 rating points.
+const EXPECTED: [i64; 33] = [
+	500_000, 535_916, 571_463, 606_288, 640_065, 672_510, 703_385, 732_507, 759_747, 785_027,
+	808_318, 829_633, 849_020, 866_557, 882_338, 896_477, 909_091, 920_305, 930_242, 939_022,
+	946_760, 953_565, 959_537, 964_769, 969_347, 973_346, 976_836, 979_878, 982_528, 984_834,
+	986_840, 988_584, 990_099,
+];
+
+/// Expected score of a player with `rating` against a player with `opponent`, in parts per
+/// million. The expected scores of both players always add up to `SCORE_ONE`.
+pub fn expected_score(rating: Rating, opponent: Rating) -> i64 {
+	let max = STEP * (EXPECTED.len() as u32 - 1);
+	let difference = if rating > opponent { rating - opponent } else { opponent - rating }.min(max);
+
+	let index = (difference / STEP) as usize;
+	let offset = (difference % STEP) as i64;
+	let expected = match EXPECTED.get(index + 1) {
+		Some(next) => EXPECTED[index] + (next - EXPECTED[index]) * offset / STEP as i64,
+		None => EXPECTED[index],
+	};
+
+	if rating >= opponent { expected } else { SCORE_ONE - expected }
+}
+
+/// Rating change of a player with `rating` who scored `score` against a player with `opponent`.
+/// Rounded half away from zero, so the changes of both players cancel each other out.
+pub fn rating_change(rating: Rating, opponent: Rating, score: i64, k_factor: u32) -> i64 {
+	let change = k_factor as i64 * (score - expected_score(rating, opponent));
+	let half = SCORE_ONE / 2;
+	if change >= 0 {
+		(change + half) / SCORE_ONE
+	} else {
+		(change - half) / SCORE_ONE
+	}
+}
+
+/// Maps ratings to `Amount` matchmaker brackets of `Width` rating points each, higher ratings
+/// fall into the last bracket.
+pub struct RatingBrackets<Width, Amount>(PhantomData<(Width, Amount)>);
+
+impl<Width: Get<Rating>, Amount: Get<u8>> Convert<Rating, u8> for RatingBrackets<Width, Amount> {
+	fn convert(rating: Rating) -> u8 {
+		let last = Amount::get().saturating_sub(1);
+		let bracket = rating.checked_div(Width::get()).unwrap_or(0);
+		bracket.min(last as Rating) as u8
+	}
+}
+
+/// Applies a rating change, ratings don't go below zero.
+pub fn apply_change(rating: Rating, change: i64) -> Rating {
+	let rating = rating as i64 + change;
+	if rating < 0 {
+		0
+	} else if rating > Rating::MAX as i64 {
+		Rating::MAX
+	} else {
+		rating as Rating
+	}
+}
@@ -0,0 +1,152 @@
+//! Rule sets and evaluation rules of a round with any amount of players.
+//!
+//! Every player whose weapon is beaten by the weapon of another player is eliminated. A round
+//! where nobody or everybody would be eliminated, like all players choosing the same weapon or
+//! rock, paper and scissor all appearing, is a draw.
+
+use frame_support::{Parameter, traits::{Get, MaxEncodedLen}};
+use sp_std::{cmp::Ordering, marker::PhantomData, vec::Vec};
+
+use crate::WeaponType;
+
+/// Weapons of a game and the relation which weapon beats which.
+pub trait RuleSet {
+	/// Weapon players can choose.
+	type Weapon: Parameter + MaxEncodedLen;
+
+	/// `Greater` if weapon `a` beats weapon `b`, `Less` if `b` beats `a`, `Equal` otherwise.
+	fn beats(a: &Self::Weapon, b: &Self::Weapon) -> Ordering;
+
+	/// Checks if the weapon is part of the rule set.
+	fn is_valid(weapon: &Self::Weapon) -> bool;
+}
+
+/// Compares two weapons of a cyclic rule set, each weapon beats the next half of the weapons
+/// before it in the cycle. With an even amount of weapons the weapon opposite in the cycle is
+/// neither beaten nor beating.
+fn cyclic_beats(a: u8, b: u8, amount: u8) -> Ordering {
+	if a == b || a >= amount || b >= amount {
+		return Ordering::Equal;
+	}
+	let distance = (a as u16 + amount as u16 - b as u16) % amount as u16;
+	let half = amount as u16 / 2;
+	if amount % 2 == 0 && distance == half {
+		Ordering::Equal
+	} else if distance <= half {
+		Ordering::Greater
+	} else {
+		Ordering::Less
+	}
+}
+
+/// Rock, paper and scissor.
+pub struct ClassicRules;
+
+impl ClassicRules {
+	fn position(weapon: &WeaponType) -> Option<u8> {
+		match weapon {
+			WeaponType::Rock => Some(0),
+			WeaponType::Paper => Some(1),
+			WeaponType::Scissor => Some(2),
+			_ => None,
+		}
+	}
+}
+
+impl RuleSet for ClassicRules {
+	type Weapon = WeaponType;
+
+	fn beats(a: &WeaponType, b: &WeaponType) -> Ordering {
+		match (Self::position(a), Self::position(b)) {
+			(Some(a), Some(b)) => cyclic_beats(a, b, 3),
+			_ => Ordering::Equal,
+		}
+	}
+
+	fn is_valid(weapon: &WeaponType) -> bool {
+		Self::position(weapon).is_some()
+	}
+}
+
+/// Rock, paper, scissor, lizard and spock.
+pub struct LizardSpockRules;
+
+impl LizardSpockRules {
+	fn position(weapon: &WeaponType) -> Option<u8> {
+		// order of the cycle, each weapon beats the two before it
+		match weapon {
+			WeaponType::Rock => Some(0),
+			WeaponType::Spock => Some(1),
+			WeaponType::Paper => Some(2),
+			WeaponType::Lizard => Some(3),
+			WeaponType::Scissor => Some(4),
+			WeaponType::None => None,
+		}
+	}
+}
+
+impl RuleSet for LizardSpockRules {
+	type Weapon = WeaponType;
+
+	fn beats(a: &WeaponType, b: &WeaponType) -> Ordering {
+		match (Self::position(a), Self::position(b)) {
+			(Some(a), Some(b)) => cyclic_beats(a, b, 5),
+			_ => Ordering::Equal,
+		}
+	}
+
+	fn is_valid(weapon: &WeaponType) -> bool {
+		Self::position(weapon).is_some()
+	}
+}
+
+/// Cycle of `Amount` weapons numbered from zero, every weapon beats as many weapons as it's
+/// beaten by. With an even `Amount` every weapon ties with the weapon opposite in the cycle.
+pub struct CyclicRules<Amount>(PhantomData<Amount>);
+
+impl<Amount: Get<u8>> RuleSet for CyclicRules<Amount> {
+	type Weapon = u8;
+
+	fn beats(a: &u8, b: &u8) -> Ordering {
+		cyclic_beats(*a, *b, Amount::get())
+	}
+
+	fn is_valid(weapon: &u8) -> bool {
+		*weapon < Amount::get()
+	}
+}
+
+/// Outcome of a single round.
+#[derive(Clone, PartialEq, Debug)]
+pub enum RoundOutcome<AccountId> {
+	/// Only one player wasn't beaten.
+	Winner(AccountId),
+	/// Several players weren't beaten, they have to play another round.
+	Survivors(Vec<AccountId>),
+	/// No player could be eliminated.
+	Draw,
+}
+
+/// Eliminates every player whose weapon is beaten, `beats(a, b)` returns true if weapon `a`
+/// beats weapon `b`. The outcome doesn't depend on the order of `choices`, survivors keep
+/// their order.
+pub fn eliminate<AccountId, Weapon, F>(
+	choices: &[(AccountId, Weapon)],
+	beats: F
+) -> RoundOutcome<AccountId>
+where
+	AccountId: Clone,
+	F: Fn(&Weapon, &Weapon) -> bool,
+{
+	let mut survivors: Vec<AccountId> = choices.iter()
+		.filter(|(_, weapon)| !choices.iter().any(|(_, other)| beats(other, weapon)))
+		.map(|(player, _)| player.clone())
+		.collect();
+
+	match survivors.len() {
+		0 => RoundOutcome::Draw,
+		1 => RoundOutcome::Winner(survivors.remove(0)),
+		n if n == choices.len() => RoundOutcome::Draw,
+		_ => RoundOutcome::Survivors(survivors),
+	}
+}
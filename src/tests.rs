@@ -1,27 +1,25 @@
 use super::*;
 use crate::{Error, mock::*};
 
-use frame_support::{assert_ok, assert_noop};
+use frame_support::{assert_ok, assert_noop, BoundedVec, traits::{Get, OnRuntimeUpgrade, MaxEncodedLen}};
+use sp_core::H256;
+use sp_std::{cmp::Ordering, convert::TryFrom};
+use sp_runtime::Perbill;
+use crate::rules::{ClassicRules, CyclicRules, LizardSpockRules, RuleSet};
+use crate::rating::{self, RatingBrackets, SCORE_ONE, SCORE_DRAW};
+use sp_runtime::traits::Convert;
+use pallet_matchmaker::MatchFunc;
 
-#[test]
-fn it_works_for_default_value() {
-	new_test_ext().execute_with(|| {
-		// Dispatch a signed extrinsic.
-		assert_ok!(RockPaperScissor::do_something(Origin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(RockPaperScissor::something(), Some(42));
-	});
+/// Bounded players of a game, or a part of them.
+fn bounded<Item>(items: Vec<Item>) -> BoundedVec<Item, MaxPlayers> {
+	BoundedVec::try_from(items).unwrap()
 }
 
-#[test]
-fn correct_error_for_none_value() {
-	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
-		assert_noop!(
-			RockPaperScissor::cause_error(Origin::signed(1)),
-			Error::<Test>::NoneValue
-		);
-	});
+/// Events deposited by the rps pallet so far.
+fn rps_events() -> Vec<crate::Event<Test>> {
+	System::events().into_iter().filter_map(|record| {
+		if let crate::mock::Event::pallet_rps(inner) = record.event { Some(inner) } else { None }
+	}).collect()
 }
 
 #[test]
@@ -107,7 +105,7 @@ fn try_simple_rps_game() {
 		current_block = current_block + 1;
 
 		// Choose phase
-		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2), WeaponType::Paper, salt_2));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2), RockPaperScissor::commitment(game_id, 0, &player_2, WeaponType::Paper, salt_2)));
 		let game = RockPaperScissor::games(game_id);
 		matches!(game.match_state, MatchState::Choose(_));
 		assert_eq!(game.last_action, current_block);
@@ -115,7 +113,7 @@ fn try_simple_rps_game() {
 		run_next_block();
 		current_block = current_block + 1;
 
-		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1), WeaponType::Scissor, salt_1));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1), RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Scissor, salt_1)));
 		let game = RockPaperScissor::games(game_id);
 		matches!(game.match_state, MatchState::Reveal(_));
 		assert_eq!(game.last_action, current_block);
@@ -144,10 +142,1850 @@ fn try_simple_rps_game() {
 		assert_eq!(game.last_action, current_block);
 
 		// finished phase
-		if let MatchState::Finished(winner) = game.match_state {
-			assert_eq!(winner, player_1);
+		if let MatchState::Finished(result) = game.match_state {
+			assert_eq!(result, GameResult::Winner(player_1));
 		} else {
 			assert!(false);
 		}
 	});
-}
\ No newline at end of file
+}
+#[test]
+fn choose_stores_commitment_only() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+		let salt: [u8; 32] = [7u8;32];
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+
+		// choosing before initiation is not allowed
+		let commitment = RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Rock, salt);
+		assert_noop!(RockPaperScissor::choose(Origin::signed(player_1), commitment),
+			Error::<Test>::BadBehaviour);
+
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+
+		// only the commitment is persisted
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1), commitment));
+		assert_eq!(RockPaperScissor::player_choice(game_id, player_1), Choice::Choose(commitment));
+
+		// a second commitment is rejected
+		assert_noop!(RockPaperScissor::choose(Origin::signed(player_1), commitment),
+			Error::<Test>::PlayerChoiceExist);
+	});
+}
+
+#[test]
+fn copied_commitments_are_rejected() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+		let player_3:u64 = 3;
+		let player_4:u64 = 4;
+		let salt: [u8; 32] = [1u8;32];
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_a = RockPaperScissor::player_game(player_1);
+		run_next_block();
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_3), player_4));
+		let game_b = RockPaperScissor::player_game(player_3);
+		assert_ne!(game_a, game_b);
+
+		// same salt and weapon give different commitments per game and player
+		let commitment = RockPaperScissor::commitment(game_a, 0, &player_1, WeaponType::Rock, salt);
+		assert_ne!(commitment, RockPaperScissor::commitment(game_a, 0, &player_2, WeaponType::Rock, salt));
+		assert_ne!(commitment, RockPaperScissor::commitment(game_b, 0, &player_1, WeaponType::Rock, salt));
+
+		for player in &[player_1, player_2, player_3, player_4] {
+			assert_ok!(RockPaperScissor::initiate(Origin::signed(*player)));
+		}
+
+		// player 2 and player 3 copy the commitment of player 1
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1), commitment));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2), commitment));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_3), commitment));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_4),
+			RockPaperScissor::commitment(game_b, 0, &player_4, WeaponType::Paper, salt)));
+
+		// player 1 reveals, which makes weapon and salt public
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_1), WeaponType::Rock, salt));
+
+		// copying the revealed values doesn't open the copied commitment
+		assert_noop!(RockPaperScissor::reveal(Origin::signed(player_2), WeaponType::Rock, salt),
+			Error::<Test>::BadBehaviour);
+		assert_noop!(RockPaperScissor::reveal(Origin::signed(player_3), WeaponType::Rock, salt),
+			Error::<Test>::BadBehaviour);
+	});
+}
+
+#[test]
+fn commitment_uses_full_salt() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let game_id = H256::repeat_byte(1);
+		let salt_1: [u8; 32] = [1u8;32];
+		let mut salt_2 = salt_1;
+		salt_2[31] = 2;
+
+		// salts only differing in the last byte give different commitments
+		assert_ne!(
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Rock, salt_1),
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Rock, salt_2)
+		);
+		// a salt ending in the weapon byte doesn't collide with another weapon
+		let mut salt_3 = salt_1;
+		salt_3[31] = WeaponType::Paper as u8;
+		assert_ne!(
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Rock, salt_3),
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Paper, salt_3)
+		);
+	});
+}
+
+#[test]
+fn initiate_timeout_ends_game() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+		let player_3:u64 = 3;
+		let player_4:u64 = 4;
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_1 = RockPaperScissor::player_game(player_1);
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_3), player_4));
+		let game_2 = RockPaperScissor::player_game(player_3);
+
+		// only player 1 initiates in time
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+
+		run_to_block(10);
+		assert!(matches!(RockPaperScissor::games(game_1).match_state, MatchState::Initiate(_)));
+
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_1).match_state, MatchState::Finished(GameResult::Forfeit(player_1)));
+		assert_eq!(RockPaperScissor::games(game_2).match_state, MatchState::Finished(GameResult::Abandoned));
+		assert_eq!(RockPaperScissor::games(game_1).last_action, 11);
+	});
+}
+
+#[test]
+fn reveal_timeout_ends_game() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+
+		// every phase gets its own timeout
+		run_to_block(9);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		run_to_block(18);
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1),
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Rock, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2),
+			RockPaperScissor::commitment(game_id, 0, &player_2, WeaponType::Paper, salt)));
+		assert!(matches!(RockPaperScissor::games(game_id).match_state, MatchState::Reveal(_)));
+
+		// player 2 would win but doesn't reveal
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_1), WeaponType::Rock, salt));
+
+		run_to_block(27);
+		assert!(matches!(RockPaperScissor::games(game_id).match_state, MatchState::Reveal(_)));
+
+		run_to_block(28);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(player_1)));
+
+		// late reveals are rejected
+		assert_noop!(RockPaperScissor::reveal(Origin::signed(player_2), WeaponType::Paper, salt),
+			Error::<Test>::BadBehaviour);
+	});
+}
+
+#[test]
+fn force_timeout_needs_root() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+
+		assert_noop!(RockPaperScissor::force_timeout(Origin::signed(player_1), game_id),
+			sp_runtime::DispatchError::BadOrigin);
+		assert_noop!(RockPaperScissor::force_timeout(Origin::root(), H256::repeat_byte(1)),
+			Error::<Test>::GameDoesntExist);
+
+		assert_ok!(RockPaperScissor::force_timeout(Origin::root(), game_id));
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Abandoned));
+
+		// a finished game can't time out again
+		assert_noop!(RockPaperScissor::force_timeout(Origin::root(), game_id),
+			Error::<Test>::NoRunningPhase);
+	});
+}
+
+#[test]
+fn finished_games_get_removed() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1),
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Rock, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2),
+			RockPaperScissor::commitment(game_id, 0, &player_2, WeaponType::Scissor, salt)));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_1), WeaponType::Rock, salt));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_2), WeaponType::Scissor, salt));
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Winner(player_1)));
+		assert_eq!(RockPaperScissor::game_cleanup(6).into_inner(), vec![game_id]);
+
+		// finished game is kept during the grace period
+		run_to_block(5);
+		assert!(Games::<Test>::contains_key(game_id));
+		assert_noop!(
+			RockPaperScissor::new_game(Origin::signed(player_1), player_2),
+			Error::<Test>::PlayerHasGame
+		);
+
+		run_to_block(6);
+		assert!(!Games::<Test>::contains_key(game_id));
+		assert!(!PlayerGame::<Test>::contains_key(player_1));
+		assert!(!PlayerGame::<Test>::contains_key(player_2));
+		assert!(!PlayerChoice::<Test>::contains_key(game_id, player_1));
+		assert!(!PlayerChoice::<Test>::contains_key(game_id, player_2));
+		assert!(!GameCleanup::<Test>::contains_key(6));
+		assert_eq!(rps_events().last(), Some(&crate::Event::GameRemoved(game_id)));
+
+		// players are free to play again
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(3)));
+	});
+}
+
+#[test]
+fn draw_is_not_a_winner() {
+	new_test_ext().execute_with(|| {
+
+		// account 0 is the default account
+		let player_1:u64 = 0;
+		let player_2:u64 = 1;
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1),
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Paper, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2),
+			RockPaperScissor::commitment(game_id, 0, &player_2, WeaponType::Paper, salt)));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_1), WeaponType::Paper, salt));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_2), WeaponType::Paper, salt));
+
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Draw));
+		assert!(rps_events().contains(&crate::Event::GameFinished(game_id, GameResult::Draw)));
+	});
+}
+
+#[test]
+fn default_account_can_win() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 0;
+		let player_2:u64 = 1;
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1),
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Scissor, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2),
+			RockPaperScissor::commitment(game_id, 0, &player_2, WeaponType::Paper, salt)));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_1), WeaponType::Scissor, salt));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(player_2), WeaponType::Paper, salt));
+
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Winner(player_1)));
+		assert!(rps_events().contains(&crate::Event::GameFinished(game_id, GameResult::Winner(player_1))));
+	});
+}
+
+/// Weapons of the classic rule set.
+const WEAPONS: [WeaponType; 3] = [WeaponType::Rock, WeaponType::Paper, WeaponType::Scissor];
+
+/// Outcome of a round following the rules in words: with exactly two different weapons
+/// the players of the winning one survive, every other round is a draw.
+fn expected_outcome(choices: &[(u64, WeaponType)]) -> RoundOutcome<u64> {
+	let present: Vec<&WeaponType> = WEAPONS.iter()
+		.filter(|weapon| choices.iter().any(|(_, choice)| choice == *weapon))
+		.collect();
+	if present.len() != 2 {
+		return RoundOutcome::Draw;
+	}
+	let winning = match (present[0], present[1]) {
+		(WeaponType::Rock, WeaponType::Paper) => WeaponType::Paper,
+		(WeaponType::Rock, WeaponType::Scissor) => WeaponType::Rock,
+		(WeaponType::Paper, WeaponType::Scissor) => WeaponType::Scissor,
+		_ => unreachable!(),
+	};
+	let mut survivors: Vec<u64> = choices.iter()
+		.filter(|(_, choice)| *choice == winning)
+		.map(|(player, _)| *player)
+		.collect();
+	if survivors.len() == 1 {
+		RoundOutcome::Winner(survivors.remove(0))
+	} else {
+		RoundOutcome::Survivors(survivors)
+	}
+}
+
+#[test]
+fn elimination_covers_all_combinations() {
+	let beats = |a: &WeaponType, b: &WeaponType| ClassicRules::beats(a, b) == Ordering::Greater;
+
+	for amount in 2..=5u32 {
+		for combination in 0..3usize.pow(amount) {
+			let mut digits = combination;
+			let choices: Vec<(u64, WeaponType)> = (1..=amount as u64).map(|player| {
+				let weapon = WEAPONS[digits % 3].clone();
+				digits /= 3;
+				(player, weapon)
+			}).collect();
+
+			let outcome = rules::eliminate(&choices, beats);
+			assert_eq!(outcome, expected_outcome(&choices), "choices {:?}", choices);
+
+			// the order of the players doesn't change the outcome
+			let mut reversed = choices.clone();
+			reversed.reverse();
+			let reversed_outcome = match rules::eliminate(&reversed, beats) {
+				RoundOutcome::Survivors(mut players) => {
+					players.reverse();
+					RoundOutcome::Survivors(players)
+				},
+				other => other,
+			};
+			assert_eq!(reversed_outcome, outcome, "choices {:?}", reversed);
+		}
+	}
+}
+
+/// Lets every player of the game commit and reveal for the current round.
+fn play_round(game_id: H256, choices: &[(u64, WeaponType)]) {
+	let salt: [u8; 32] = [1u8;32];
+	let round = RockPaperScissor::games(game_id).round;
+	for (player, weapon) in choices {
+		assert_ok!(RockPaperScissor::choose(Origin::signed(*player),
+			RockPaperScissor::commitment(game_id, round, player, weapon.clone(), salt)));
+	}
+	for (player, weapon) in choices {
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(*player), weapon.clone(), salt));
+	}
+}
+
+#[test]
+fn three_player_game_eliminates_beaten_players() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let game_id = RockPaperScissor::create_game(bounded(vec![1, 2, 3]), 1, 0);
+		for player in 1..=3 {
+			assert_ok!(RockPaperScissor::initiate(Origin::signed(player)));
+		}
+
+		// scissor gets eliminated, the rocks play another round
+		play_round(game_id, &[(1, WeaponType::Rock), (2, WeaponType::Rock), (3, WeaponType::Scissor)]);
+		let game = RockPaperScissor::games(game_id);
+		assert_eq!(game.match_state, MatchState::Choose(bounded(vec![1, 2])));
+		assert_eq!(game.round, 1);
+		assert!(!PlayerChoice::<Test>::contains_key(game_id, 1));
+
+		// eliminated player can't choose anymore
+		assert_noop!(RockPaperScissor::choose(Origin::signed(3), H256::repeat_byte(3)),
+			Error::<Test>::BadBehaviour);
+
+		play_round(game_id, &[(1, WeaponType::Paper), (2, WeaponType::Rock)]);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Winner(1)));
+	});
+}
+
+#[test]
+fn three_player_game_with_all_weapons_is_a_draw() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let game_id = RockPaperScissor::create_game(bounded(vec![1, 2, 3]), 1, 0);
+		for player in 1..=3 {
+			assert_ok!(RockPaperScissor::initiate(Origin::signed(player)));
+		}
+
+		play_round(game_id, &[(1, WeaponType::Rock), (2, WeaponType::Paper), (3, WeaponType::Scissor)]);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Draw));
+	});
+}
+
+#[test]
+fn series_needs_majority_of_rounds() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+
+		run_to_block(1);
+
+		assert_noop!(RockPaperScissor::new_series(Origin::signed(player_1), player_2, 0),
+			Error::<Test>::InvalidRounds);
+		assert_noop!(RockPaperScissor::new_series(Origin::signed(player_1), player_1, 3),
+			Error::<Test>::NoFakePlay);
+
+		assert_ok!(RockPaperScissor::new_series(Origin::signed(player_1), player_2, 3));
+		let game_id = RockPaperScissor::player_game(player_1);
+
+		// first round goes to player 1
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		play_round(game_id, &[(player_1, WeaponType::Rock), (player_2, WeaponType::Scissor)]);
+		let game = RockPaperScissor::games(game_id);
+		assert_eq!(game.match_state, MatchState::Initiate(bounded(vec![player_1, player_2])));
+		assert_eq!(game.scores, bounded(vec![(player_1, 1), (player_2, 0)]));
+		assert_eq!(game.round, 1);
+		assert!(!PlayerChoice::<Test>::contains_key(game_id, player_1));
+
+		// draw gets replayed
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		play_round(game_id, &[(player_1, WeaponType::Rock), (player_2, WeaponType::Rock)]);
+		let game = RockPaperScissor::games(game_id);
+		assert_eq!(game.match_state, MatchState::Initiate(bounded(vec![player_1, player_2])));
+		assert_eq!(game.scores, bounded(vec![(player_1, 1), (player_2, 0)]));
+		assert_eq!(game.round, 2);
+
+		// second round goes to player 2
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		play_round(game_id, &[(player_1, WeaponType::Rock), (player_2, WeaponType::Paper)]);
+		assert_eq!(RockPaperScissor::games(game_id).scores, bounded(vec![(player_1, 1), (player_2, 1)]));
+
+		// third round decides the series
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		play_round(game_id, &[(player_1, WeaponType::Scissor), (player_2, WeaponType::Paper)]);
+		let game = RockPaperScissor::games(game_id);
+		assert_eq!(game.match_state, MatchState::Finished(GameResult::Winner(player_1)));
+		assert_eq!(game.scores, bounded(vec![(player_1, 2), (player_2, 1)]));
+
+		let events = rps_events();
+		assert!(events.contains(&crate::Event::RoundWon(game_id, 0, player_1)));
+		assert!(events.contains(&crate::Event::RoundDrawn(game_id, 1)));
+		assert!(events.contains(&crate::Event::RoundWon(game_id, 2, player_2)));
+		assert!(events.contains(&crate::Event::RoundWon(game_id, 3, player_1)));
+	});
+}
+
+#[test]
+fn drawn_game_gets_rematched() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+
+		RematchOnDraw::set(true);
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+
+		// draw sends players back to choose
+		play_round(game_id, &[(player_1, WeaponType::Rock), (player_2, WeaponType::Rock)]);
+		let game = RockPaperScissor::games(game_id);
+		assert_eq!(game.match_state, MatchState::Choose(bounded(vec![player_1, player_2])));
+		assert_eq!(game.round, 1);
+		assert_eq!(game.redraws, 1);
+		assert_eq!(RockPaperScissor::player_game(player_1), game_id);
+		assert!(rps_events().contains(&crate::Event::RoundDrawn(game_id, 0)));
+
+		play_round(game_id, &[(player_1, WeaponType::Paper), (player_2, WeaponType::Rock)]);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Winner(player_1)));
+	});
+}
+
+#[test]
+fn rematch_after_reveal_timeout_keeps_its_timeouts() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		RematchOnDraw::set(true);
+		run_to_block(1);
+
+		let game_id = RockPaperScissor::create_game(bounded(vec![1, 2, 3]), 1, 0);
+		for player in 1..=3 {
+			assert_ok!(RockPaperScissor::initiate(Origin::signed(player)));
+		}
+		for (player, weapon) in &[(1, WeaponType::Rock), (2, WeaponType::Rock), (3, WeaponType::Paper)] {
+			assert_ok!(RockPaperScissor::choose(Origin::signed(*player),
+				RockPaperScissor::commitment(game_id, 0, player, weapon.clone(), salt)));
+		}
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(1), WeaponType::Rock, salt));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(2), WeaponType::Rock, salt));
+
+		// player 3 doesn't reveal, the revealed rocks draw and get rematched
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Choose(bounded(vec![1, 2])));
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), Some(21));
+
+		run_to_block(12);
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 1, &1, WeaponType::Paper, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(2),
+			RockPaperScissor::commitment(game_id, 1, &2, WeaponType::Rock, salt)));
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), Some(22));
+
+		// the choose timeout of the rematch got cancelled
+		run_to_block(21);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Reveal(bounded(vec![1, 2])));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(1), WeaponType::Paper, salt));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(2), WeaponType::Rock, salt));
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Winner(1)));
+		// the slashed bond of player 3 got shared by the players who revealed
+		assert_eq!(Balances::free_balance(2), 1_005);
+	});
+}
+
+#[test]
+fn committed_players_reveal_after_choose_timeout() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+		let issuance = Balances::total_issuance();
+
+		let game_id = RockPaperScissor::create_game(bounded(vec![1, 2, 3]), 1, 0);
+		for player in 1..=3 {
+			assert_ok!(RockPaperScissor::initiate(Origin::signed(player)));
+		}
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Paper, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(2),
+			RockPaperScissor::commitment(game_id, 0, &2, WeaponType::Rock, salt)));
+
+		// player 3 doesn't choose, the others go on to reveal and share its bond
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Reveal(bounded(vec![1, 2])));
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), Some(21));
+		assert_eq!(Balances::free_balance(1), 995);
+		assert_eq!(Balances::free_balance(2), 995);
+		assert_eq!(Balances::free_balance(3), 990);
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_eq!(rps_events().last(), Some(&crate::Event::PhaseAdvanced(game_id, Phase::Choose, Phase::Reveal)));
+
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(1), WeaponType::Paper, salt));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(2), WeaponType::Rock, salt));
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Winner(1)));
+		assert_eq!(Balances::free_balance(1), 1_005);
+		assert_eq!(Balances::free_balance(2), 1_005);
+		assert_eq!(Balances::total_issuance(), issuance);
+	});
+}
+
+#[test]
+fn rematches_are_limited() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+
+		RematchOnDraw::set(true);
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+
+		for redraws in 1..=MaxRedraws::get() {
+			play_round(game_id, &[(player_1, WeaponType::Paper), (player_2, WeaponType::Paper)]);
+			let game = RockPaperScissor::games(game_id);
+			assert_eq!(game.match_state, MatchState::Choose(bounded(vec![player_1, player_2])));
+			assert_eq!(game.redraws, redraws);
+		}
+
+		// limit reached, game ends in a draw
+		play_round(game_id, &[(player_1, WeaponType::Paper), (player_2, WeaponType::Paper)]);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Draw));
+	});
+}
+
+#[test]
+fn lizard_spock_rules() {
+	use WeaponType::*;
+
+	// every relation of the game, winner first
+	let wins = [
+		(Scissor, Paper), (Paper, Rock), (Rock, Lizard), (Lizard, Spock), (Spock, Scissor),
+		(Scissor, Lizard), (Lizard, Paper), (Paper, Spock), (Spock, Rock), (Rock, Scissor),
+	];
+	let weapons = [Rock, Paper, Scissor, Lizard, Spock];
+
+	for a in &weapons {
+		for b in &weapons {
+			let expected = if a == b {
+				Ordering::Equal
+			} else if wins.contains(&(a.clone(), b.clone())) {
+				Ordering::Greater
+			} else {
+				Ordering::Less
+			};
+			assert_eq!(LizardSpockRules::beats(a, b), expected, "{:?} vs {:?}", a, b);
+		}
+		assert!(LizardSpockRules::is_valid(a));
+	}
+	assert!(!LizardSpockRules::is_valid(&None));
+
+	// classic rules only know three weapons
+	assert!(!ClassicRules::is_valid(&Lizard));
+	assert!(!ClassicRules::is_valid(&Spock));
+	assert!(!ClassicRules::is_valid(&None));
+	assert_eq!(ClassicRules::beats(&Rock, &Scissor), Ordering::Greater);
+	assert_eq!(ClassicRules::beats(&Rock, &Paper), Ordering::Less);
+}
+
+frame_support::parameter_types! {
+	pub const SevenWeapons: u8 = 7;
+	pub const SixWeapons: u8 = 6;
+}
+
+#[test]
+fn cyclic_rules_are_balanced() {
+	type Rules = CyclicRules<SevenWeapons>;
+
+	for a in 0..7u8 {
+		assert!(Rules::is_valid(&a));
+		let beaten = (0..7u8).filter(|b| Rules::beats(&a, b) == Ordering::Greater).count();
+		assert_eq!(beaten, 3);
+		for b in 0..7u8 {
+			assert_eq!(Rules::beats(&a, &b), Rules::beats(&b, &a).reverse());
+		}
+	}
+	assert!(!Rules::is_valid(&7));
+}
+
+#[test]
+fn cyclic_rules_with_even_amount_are_balanced() {
+	type Rules = CyclicRules<SixWeapons>;
+
+	for a in 0..6u8 {
+		let beaten = (0..6u8).filter(|b| Rules::beats(&a, b) == Ordering::Greater).count();
+		assert_eq!(beaten, 2);
+		for b in 0..6u8 {
+			assert_eq!(Rules::beats(&a, &b), Rules::beats(&b, &a).reverse());
+		}
+	}
+	// the opposite weapon ties
+	assert_eq!(Rules::beats(&0, &3), Ordering::Equal);
+	assert_eq!(Rules::beats(&4, &1), Ordering::Equal);
+}
+
+#[test]
+fn reveal_checks_weapon() {
+	new_test_ext().execute_with(|| {
+
+		let player_1:u64 = 1;
+		let player_2:u64 = 2;
+		let salt: [u8; 32] = [1u8;32];
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(player_1), player_2));
+		let game_id = RockPaperScissor::player_game(player_1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(player_2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_1),
+			RockPaperScissor::commitment(game_id, 0, &player_1, WeaponType::Spock, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(player_2),
+			RockPaperScissor::commitment(game_id, 0, &player_2, WeaponType::None, salt)));
+
+		// weapons outside of the classic rules can't be revealed
+		assert_noop!(RockPaperScissor::reveal(Origin::signed(player_1), WeaponType::Spock, salt),
+			Error::<Test>::InvalidWeapon);
+		assert_noop!(RockPaperScissor::reveal(Origin::signed(player_2), WeaponType::None, salt),
+			Error::<Test>::InvalidWeapon);
+	});
+}
+
+/// Plays a full two player game, player 1 with `weapon_1` and player 2 with `weapon_2`.
+fn play_game(game_id: H256, weapon_1: WeaponType, weapon_2: WeaponType) {
+	assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+	assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+	play_round(game_id, &[(1, weapon_1), (2, weapon_2)]);
+}
+
+#[test]
+fn wagered_game_pays_winner() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_eq!(Balances::reserved_balance(1), 100);
+		assert_eq!(Balances::reserved_balance(2), 0);
+
+		// opponent stakes when joining, both reserve the participation bond
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_eq!(Balances::reserved_balance(2), 110);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(1), 110);
+
+		play_round(game_id, &[(1, WeaponType::Paper), (2, WeaponType::Rock)]);
+
+		assert_eq!(Balances::free_balance(1), 1_100);
+		assert_eq!(Balances::free_balance(2), 900);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+
+		let events = rps_events();
+		assert!(events.contains(&crate::Event::StakeReserved(game_id, 1, 100)));
+		assert!(events.contains(&crate::Event::StakeReserved(game_id, 2, 100)));
+		assert!(events.contains(&crate::Event::StakePaid(game_id, 2, 1, 100)));
+		assert!(events.contains(&crate::Event::StakeReleased(game_id, 1, 100)));
+	});
+}
+
+#[test]
+fn wagered_draw_returns_stakes() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Rock, WeaponType::Rock);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Draw));
+
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Balances::free_balance(2), 1_000);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+	});
+}
+
+#[test]
+fn wagered_forfeit_pays_stake() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Rock, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(2),
+			RockPaperScissor::commitment(game_id, 0, &2, WeaponType::Paper, salt)));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(1), WeaponType::Rock, salt));
+
+		// player 2 doesn't reveal in time and loses stake and bond
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(1)));
+		assert_eq!(Balances::free_balance(1), 1_110);
+		assert_eq!(Balances::free_balance(2), 890);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+	});
+}
+
+#[test]
+fn abandoned_wager_returns_stake() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+
+		// nobody initiates
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Abandoned));
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(2), 1_000);
+	});
+}
+
+#[test]
+fn wager_needs_balance() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		// creator can't afford the stake
+		assert_noop!(RockPaperScissor::new_wagered_game(Origin::signed(9), 2, 100),
+			Error::<Test>::InsufficientBalance);
+
+		// opponent can't afford the stake
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 9, 100));
+		assert_noop!(RockPaperScissor::initiate(Origin::signed(9)),
+			Error::<Test>::InsufficientBalance);
+		assert_eq!(Balances::free_balance(9), 10);
+	});
+}
+
+#[test]
+fn wager_needs_stake() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_noop!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 0),
+			Error::<Test>::InvalidStake);
+	});
+}
+
+#[test]
+fn bonds_get_returned() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(1), ParticipationBond::get());
+
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		play_round(game_id, &[(1, WeaponType::Paper), (2, WeaponType::Rock)]);
+
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Balances::free_balance(2), 1_000);
+		assert!(rps_events().contains(&crate::Event::BondReleased(game_id, 2, 10)));
+	});
+}
+
+#[test]
+fn late_players_get_slashed() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+		let issuance = Balances::total_issuance();
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Rock, salt)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(2),
+			RockPaperScissor::commitment(game_id, 0, &2, WeaponType::Paper, salt)));
+
+		// nobody reveals, both bonds go to the slash handler
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Abandoned));
+		assert_eq!(Balances::free_balance(1), 990);
+		assert_eq!(Balances::free_balance(2), 990);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::total_issuance(), issuance - 20);
+
+		let events = rps_events();
+		assert!(events.contains(&crate::Event::BondSlashed(game_id, 1, 10)));
+		assert!(events.contains(&crate::Event::BondSlashed(game_id, 2, 10)));
+	});
+}
+
+#[test]
+fn slashed_bond_goes_to_honest_player() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Rock, salt)));
+
+		// player 2 doesn't commit in time
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(1)));
+		assert_eq!(Balances::free_balance(1), 1_010);
+		assert_eq!(Balances::free_balance(2), 990);
+	});
+}
+
+#[test]
+fn house_fee_is_rounded_down() {
+	new_test_ext().execute_with(|| {
+
+		HouseFee::set(Perbill::from_percent(5));
+		run_to_block(1);
+
+		// 5% of a pot of 42 is 2.1
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 21));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Scissor, WeaponType::Paper);
+
+		assert_eq!(Balances::free_balance(1), 1_019);
+		assert_eq!(Balances::free_balance(2), 979);
+		assert_eq!(Balances::free_balance(HOUSE), 2);
+		assert!(rps_events().contains(&crate::Event::GameSettled(game_id, 1, 40, 2)));
+	});
+}
+
+#[test]
+fn house_fee_below_one_unit_is_dropped() {
+	new_test_ext().execute_with(|| {
+
+		HouseFee::set(Perbill::from_percent(5));
+		run_to_block(1);
+
+		// 5% of a pot of 14 is 0.7
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 7));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Rock, WeaponType::Paper);
+
+		assert_eq!(Balances::free_balance(1), 993);
+		assert_eq!(Balances::free_balance(2), 1_007);
+		assert_eq!(Balances::free_balance(HOUSE), 0);
+		assert!(rps_events().contains(&crate::Event::GameSettled(game_id, 2, 14, 0)));
+	});
+}
+
+#[test]
+fn house_fee_larger_than_losers_stake() {
+	new_test_ext().execute_with(|| {
+
+		HouseFee::set(Perbill::from_percent(75));
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Paper, WeaponType::Rock);
+
+		// winner pays the part of the fee the loser's stake doesn't cover
+		assert_eq!(Balances::free_balance(1), 950);
+		assert_eq!(Balances::free_balance(2), 900);
+		assert_eq!(Balances::free_balance(HOUSE), 150);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert!(rps_events().contains(&crate::Event::GameSettled(game_id, 1, 50, 150)));
+	});
+}
+
+#[test]
+fn lone_stake_has_no_house_fee() {
+	new_test_ext().execute_with(|| {
+
+		HouseFee::set(Perbill::from_percent(5));
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+
+		// the opponent never initiates, so only the stake of the creator is in the pot
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(1)));
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(HOUSE), 0);
+
+		let events = rps_events();
+		assert!(events.contains(&crate::Event::StakeReleased(game_id, 1, 100)));
+		assert!(!events.iter().any(|event| matches!(event, crate::Event::GameSettled(..))));
+	});
+}
+
+#[test]
+fn draw_has_no_house_fee() {
+	new_test_ext().execute_with(|| {
+
+		HouseFee::set(Perbill::from_percent(5));
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Paper, WeaponType::Paper);
+
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Balances::free_balance(2), 1_000);
+		assert_eq!(Balances::free_balance(HOUSE), 0);
+	});
+}
+
+#[test]
+fn expected_score_is_symmetric() {
+	assert_eq!(rating::expected_score(1500, 1500), 500_000);
+	assert_eq!(rating::expected_score(1900, 1500), 909_091);
+	assert_eq!(rating::expected_score(1500, 1900), 90_909);
+	// interpolated between the table entries of 0 and 25 points
+	assert_eq!(rating::expected_score(1510, 1500), 514_366);
+	// differences beyond 800 points are capped
+	assert_eq!(rating::expected_score(3000, 1000), 990_099);
+	assert_eq!(rating::expected_score(0, 3000), 9_901);
+
+	for (a, b) in [(1500, 1500), (1513, 1488), (1200, 1999), (0, 4000), (2717, 2403)].iter() {
+		assert_eq!(rating::expected_score(*a, *b) + rating::expected_score(*b, *a), SCORE_ONE);
+	}
+}
+
+#[test]
+fn rating_changes_cancel_out() {
+	assert_eq!(rating::rating_change(1500, 1500, SCORE_ONE, 32), 16);
+	assert_eq!(rating::rating_change(1500, 1500, SCORE_DRAW, 32), 0);
+	// 32 * (1 - 0.759747) = 7.69
+	assert_eq!(rating::rating_change(1600, 1400, SCORE_ONE, 32), 8);
+	assert_eq!(rating::rating_change(1400, 1600, 0, 32), -8);
+	// underdog gains from a draw
+	assert_eq!(rating::rating_change(1400, 1600, SCORE_DRAW, 32), 8);
+
+	for (a, b) in [(1500, 1500), (1513, 1488), (1200, 1999), (0, 4000), (2717, 2403)].iter() {
+		for score in [0, SCORE_DRAW, SCORE_ONE].iter() {
+			assert_eq!(
+				rating::rating_change(*a, *b, *score, 32),
+				-rating::rating_change(*b, *a, SCORE_ONE - score, 32)
+			);
+		}
+	}
+
+	assert_eq!(rating::apply_change(1500, -16), 1484);
+	assert_eq!(rating::apply_change(5, -16), 0);
+}
+
+#[test]
+fn finished_game_updates_ratings() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_eq!(RockPaperScissor::player_rating(1), 1500);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Rock, WeaponType::Scissor);
+
+		assert_eq!(RockPaperScissor::player_rating(1), 1516);
+		assert_eq!(RockPaperScissor::player_rating(2), 1484);
+		let events = rps_events();
+		assert!(events.contains(&crate::Event::RatingUpdated(1, 1500, 1516)));
+		assert!(events.contains(&crate::Event::RatingUpdated(2, 1500, 1484)));
+
+		// the rematch is won by the lower rated player, who gains more than the first winner
+		run_to_block(6);
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Rock, WeaponType::Paper);
+
+		// 32 * (1 - 0.454131) = 17.47
+		assert_eq!(RockPaperScissor::player_rating(1), 1499);
+		assert_eq!(RockPaperScissor::player_rating(2), 1501);
+	});
+}
+
+#[test]
+fn draw_moves_ratings_towards_each_other() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		PlayerRating::<Test>::insert(1, 1600);
+		PlayerRating::<Test>::insert(2, 1400);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Paper, WeaponType::Paper);
+
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Draw));
+		assert_eq!(RockPaperScissor::player_rating(1), 1592);
+		assert_eq!(RockPaperScissor::player_rating(2), 1408);
+	});
+}
+
+#[test]
+fn abandoned_game_is_not_rated() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Abandoned));
+		assert!(!PlayerRating::<Test>::contains_key(1));
+		assert!(!PlayerRating::<Test>::contains_key(2));
+		assert!(!rps_events().iter().any(|event| matches!(event, crate::Event::RatingUpdated(..))));
+	});
+}
+
+#[test]
+fn forfeit_is_rated_as_win() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Rock, salt)));
+
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(1)));
+		assert_eq!(RockPaperScissor::player_rating(1), 1516);
+		assert_eq!(RockPaperScissor::player_rating(2), 1484);
+	});
+}
+
+#[test]
+fn forfeit_before_initiation_is_not_rated() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(1)));
+		assert_eq!(RockPaperScissor::player_rating(1), 1500);
+		assert_eq!(RockPaperScissor::player_rating(2), 1500);
+		assert!(!rps_events().iter().any(|event| matches!(event, crate::Event::RatingUpdated(..))));
+	});
+}
+
+#[test]
+fn stalling_series_round_slashes_bond() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_series(Origin::signed(1), 2, 3));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Rock, WeaponType::Scissor);
+
+		// player 2 doesn't initiate the second round, its bond of the first round goes to player 1
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		run_to_block(11);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(1)));
+		assert!(rps_events().contains(&crate::Event::BondSlashed(game_id, 2, 10)));
+		assert_eq!(Balances::free_balance(1), 1_010);
+		assert_eq!(Balances::free_balance(2), 990);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(RockPaperScissor::player_rating(2), 1484);
+	});
+}
+
+#[test]
+fn rating_brackets_cap_at_last_bracket() {
+	type Brackets = RatingBrackets<BracketWidth, AmountBrackets>;
+	assert_eq!(Brackets::convert(0), 0);
+	assert_eq!(Brackets::convert(1599), 0);
+	assert_eq!(Brackets::convert(1600), 1);
+	assert_eq!(Brackets::convert(u32::MAX), 1);
+}
+
+#[test]
+fn queue_matches_players_of_same_bracket() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		PlayerRating::<Test>::insert(1, 1700);
+		PlayerRating::<Test>::insert(4, 1650);
+
+		// different brackets are never matched
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(2)));
+		run_to_block(2);
+		assert!(!PlayerGame::<Test>::contains_key(1));
+		assert!(!PlayerGame::<Test>::contains_key(2));
+		assert!(MatchMaker::is_queued(1));
+		assert!(MatchMaker::is_queued(2));
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(3)));
+		run_to_block(3);
+		assert!(PlayerGame::<Test>::contains_key(2));
+		assert_eq!(RockPaperScissor::player_game(2), RockPaperScissor::player_game(3));
+		assert!(!PlayerGame::<Test>::contains_key(1));
+		assert!(MatchMaker::is_queued(1));
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(4)));
+		run_to_block(4);
+		assert!(PlayerGame::<Test>::contains_key(1));
+		assert_eq!(RockPaperScissor::player_game(1), RockPaperScissor::player_game(4));
+		assert!(!MatchMaker::is_queued(4));
+	});
+}
+
+type Queue = <Test as Config>::MatchMaker;
+
+#[test]
+fn dequeue_leaves_queue() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert!(Queue::is_queued(1));
+		assert_ok!(RockPaperScissor::dequeue(Origin::signed(1)));
+		assert!(!Queue::is_queued(1));
+		assert_noop!(RockPaperScissor::dequeue(Origin::signed(1)), Error::<Test>::NotQueued);
+
+		// player who left isn't matched
+		assert_ok!(RockPaperScissor::queue(Origin::signed(2)));
+		run_to_block(2);
+		assert!(!PlayerGame::<Test>::contains_key(1));
+		assert!(!PlayerGame::<Test>::contains_key(2));
+		assert!(!Dequeued::<Test>::contains_key(1));
+		assert!(Queue::is_queued(2));
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(3)));
+		run_to_block(3);
+		assert!(PlayerGame::<Test>::contains_key(2));
+		assert_eq!(RockPaperScissor::player_game(2), RockPaperScissor::player_game(3));
+		assert!(!PlayerGame::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn dropped_players_are_counted() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::dequeue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(3)));
+
+		let (mut matched, dropped) = Queue::try_match_dropping(u32::MAX);
+		matched.sort();
+		assert_eq!(matched, vec![2, 3]);
+		assert_eq!(dropped, 1);
+		assert_eq!(Queue::try_match_dropping(u32::MAX), (vec![], 0));
+	});
+}
+
+#[test]
+fn dropped_players_are_limited_per_block() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		for player in 1..=3 {
+			assert_ok!(RockPaperScissor::queue(Origin::signed(player)));
+			assert_ok!(RockPaperScissor::dequeue(Origin::signed(player)));
+		}
+		assert_ok!(RockPaperScissor::queue(Origin::signed(4)));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(5)));
+
+		// two players who left are dropped, matching continues in the next block
+		run_to_block(2);
+		assert!(!Dequeued::<Test>::contains_key(1));
+		assert!(!Dequeued::<Test>::contains_key(2));
+		assert!(Dequeued::<Test>::contains_key(3));
+		assert!(!PlayerGame::<Test>::contains_key(4));
+
+		run_to_block(3);
+		assert!(!Dequeued::<Test>::contains_key(3));
+		assert_eq!(RockPaperScissor::player_game(4), RockPaperScissor::player_game(5));
+		assert!(PlayerGame::<Test>::contains_key(4));
+	});
+}
+
+#[test]
+fn dequeue_needs_queued_player() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_noop!(RockPaperScissor::dequeue(Origin::signed(1)), Error::<Test>::NotQueued);
+
+		// players of a game aren't queued
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		assert_noop!(RockPaperScissor::dequeue(Origin::signed(1)), Error::<Test>::NotQueued);
+	});
+}
+
+#[test]
+fn dequeued_player_can_queue_again() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::dequeue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_noop!(RockPaperScissor::queue(Origin::signed(1)), Error::<Test>::AlreadyQueued);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(2)));
+		run_to_block(2);
+		assert!(PlayerGame::<Test>::contains_key(1));
+		assert_eq!(RockPaperScissor::player_game(1), RockPaperScissor::player_game(2));
+	});
+}
+
+#[test]
+fn dequeued_player_waits_for_old_bracket_after_rating_change() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::dequeue(Origin::signed(1)));
+
+		// the player moves up a bracket while out of the queue
+		PlayerRating::<Test>::insert(1, 1_700);
+		assert_noop!(RockPaperScissor::queue(Origin::signed(1)), Error::<Test>::StaleQueueEntry);
+		assert!(!Queue::is_queued(1));
+
+		// the old entry is dropped, it isn't matched with the old bracket
+		assert_ok!(RockPaperScissor::queue(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(3)));
+		run_to_block(2);
+		assert!(!PlayerGame::<Test>::contains_key(1));
+		assert!(!Dequeued::<Test>::contains_key(1));
+		assert_eq!(RockPaperScissor::player_game(2), RockPaperScissor::player_game(3));
+
+		// the player queues in the new bracket
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert!(Queue::is_queued(1));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(4)));
+		run_to_block(3);
+		assert!(!PlayerGame::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn dequeued_player_can_create_game() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_noop!(RockPaperScissor::new_game(Origin::signed(1), 2), Error::<Test>::AlreadyQueued);
+		assert_ok!(RockPaperScissor::dequeue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+
+		// the game player is dropped once matched
+		assert_ok!(RockPaperScissor::queue(Origin::signed(3)));
+		run_to_block(2);
+		assert!(!Dequeued::<Test>::contains_key(1));
+		assert!(!PlayerGame::<Test>::contains_key(3));
+		assert!(Queue::is_queued(3));
+	});
+}
+
+#[test]
+fn empty_queue_needs_admin_origin() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_noop!(RockPaperScissor::empty_queue(Origin::signed(1)), sp_runtime::DispatchError::BadOrigin);
+		assert!(Queue::is_queued(1));
+
+		assert_ok!(RockPaperScissor::empty_queue(Origin::root()));
+		assert!(!Queue::is_queued(1));
+	});
+}
+
+#[test]
+fn migration_removes_founder_key() {
+	new_test_ext().execute_with(|| {
+
+		frame_support::migration::put_storage_value(b"RockPaperScissor", b"FounderKey", &[], 1u64);
+		assert!(frame_support::migration::have_storage_value(b"RockPaperScissor", b"FounderKey", &[]));
+
+		migrations::RemoveFounderKey::<Test>::on_runtime_upgrade();
+
+		assert!(!frame_support::migration::have_storage_value(b"RockPaperScissor", b"FounderKey", &[]));
+	});
+}
+
+#[test]
+fn pause_blocks_play() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Rock, salt)));
+
+		assert_noop!(RockPaperScissor::set_paused(Origin::signed(1), true, 0), sp_runtime::DispatchError::BadOrigin);
+		assert_ok!(RockPaperScissor::set_paused(Origin::root(), true, 0));
+		assert_eq!(rps_events().last(), Some(&crate::Event::Paused));
+
+		assert_noop!(RockPaperScissor::new_game(Origin::signed(3), 4), Error::<Test>::Paused);
+		assert_noop!(RockPaperScissor::new_series(Origin::signed(3), 4, 3), Error::<Test>::Paused);
+		assert_noop!(RockPaperScissor::new_wagered_game(Origin::signed(3), 4, 100), Error::<Test>::Paused);
+		assert_noop!(RockPaperScissor::queue(Origin::signed(3)), Error::<Test>::Paused);
+		assert_noop!(RockPaperScissor::initiate(Origin::signed(1)), Error::<Test>::Paused);
+		assert_noop!(RockPaperScissor::choose(Origin::signed(2),
+			RockPaperScissor::commitment(game_id, 0, &2, WeaponType::Paper, salt)), Error::<Test>::Paused);
+		assert_noop!(RockPaperScissor::reveal(Origin::signed(1), WeaponType::Rock, salt), Error::<Test>::Paused);
+
+		assert_ok!(RockPaperScissor::set_paused(Origin::root(), false, 1));
+		assert_eq!(rps_events().last(), Some(&crate::Event::Resumed));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(2),
+			RockPaperScissor::commitment(game_id, 0, &2, WeaponType::Paper, salt)));
+	});
+}
+
+#[test]
+fn pause_stops_matchmaking() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::queue(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::set_paused(Origin::root(), true, 0));
+
+		run_to_block(3);
+		assert!(!PlayerGame::<Test>::contains_key(1));
+		assert!(Queue::is_queued(1));
+
+		// queued players can still leave
+		assert_ok!(RockPaperScissor::dequeue(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::set_paused(Origin::root(), false, 0));
+		assert_ok!(RockPaperScissor::queue(Origin::signed(2)));
+
+		run_to_block(4);
+		assert!(PlayerGame::<Test>::contains_key(1));
+		assert_eq!(RockPaperScissor::player_game(1), RockPaperScissor::player_game(2));
+	});
+}
+
+#[test]
+fn pause_freezes_timeouts() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+
+		run_to_block(2);
+		assert_ok!(RockPaperScissor::set_paused(Origin::root(), true, 0));
+
+		// the initiate timeout passes while paused
+		run_to_block(20);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Initiate(bounded(vec![1, 2])));
+
+		// resuming needs a witness of the running games
+		assert_eq!(RockPaperScissor::running_games(), 1);
+		assert_noop!(RockPaperScissor::set_paused(Origin::root(), false, 0), Error::<Test>::InvalidWitness);
+
+		// players get a full phase after resuming
+		assert_ok!(RockPaperScissor::set_paused(Origin::root(), false, 1));
+		assert_eq!(RockPaperScissor::games(game_id).last_action, 20);
+		run_to_block(29);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Initiate(bounded(vec![2])));
+
+		run_to_block(30);
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Forfeit(1)));
+		assert_eq!(RockPaperScissor::running_games(), 0);
+	});
+}
+
+#[test]
+fn runtime_api_queries() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let unknown = H256::repeat_byte(1);
+		assert_eq!(RockPaperScissor::game_of(1), None);
+		assert_eq!(RockPaperScissor::game(unknown), None);
+		assert!(RockPaperScissor::pending_players(unknown).is_empty());
+		assert_eq!(RockPaperScissor::phase_deadline(unknown), None);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_eq!(RockPaperScissor::game_of(1), Some(game_id));
+		assert_eq!(RockPaperScissor::game_of(2), Some(game_id));
+		assert_eq!(RockPaperScissor::game(game_id), Some(RockPaperScissor::games(game_id)));
+		assert_eq!(RockPaperScissor::pending_players(game_id), vec![1, 2]);
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), Some(11));
+
+		run_to_block(3);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_eq!(RockPaperScissor::pending_players(game_id), vec![2]);
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), Some(11));
+
+		// a new phase has a new deadline
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_eq!(RockPaperScissor::pending_players(game_id), vec![1, 2]);
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), Some(13));
+
+		play_round(game_id, &[(1, WeaponType::Rock), (2, WeaponType::Scissor)]);
+		assert!(RockPaperScissor::pending_players(game_id).is_empty());
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), None);
+		assert_eq!(RockPaperScissor::game_of(1), Some(game_id));
+
+		// finished games are gone after the cleanup delay
+		run_to_block(8);
+		assert_eq!(RockPaperScissor::game_of(1), None);
+		assert_eq!(RockPaperScissor::game(game_id), None);
+	});
+}
+
+#[test]
+fn game_events_in_order() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		let commitment_1 = RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Rock, salt);
+		let commitment_2 = RockPaperScissor::commitment(game_id, 0, &2, WeaponType::Scissor, salt);
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1), commitment_1));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(2), commitment_2));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(1), WeaponType::Rock, salt));
+		assert_ok!(RockPaperScissor::reveal(Origin::signed(2), WeaponType::Scissor, salt));
+
+		assert_eq!(rps_events(), vec![
+			crate::Event::GameCreated(game_id, vec![1, 2], 1, 0),
+			crate::Event::BondReserved(game_id, 1, 10),
+			crate::Event::PlayerInitiated(game_id, 1),
+			crate::Event::BondReserved(game_id, 2, 10),
+			crate::Event::PlayerInitiated(game_id, 2),
+			crate::Event::PhaseAdvanced(game_id, Phase::Initiate, Phase::Choose),
+			crate::Event::PlayerCommitted(game_id, 1, commitment_1),
+			crate::Event::PlayerCommitted(game_id, 2, commitment_2),
+			crate::Event::PhaseAdvanced(game_id, Phase::Choose, Phase::Reveal),
+			crate::Event::PlayerRevealed(game_id, 1, WeaponType::Rock),
+			crate::Event::PlayerRevealed(game_id, 2, WeaponType::Scissor),
+			crate::Event::RoundWon(game_id, 0, 1),
+			crate::Event::PhaseAdvanced(game_id, Phase::Reveal, Phase::Finished),
+			crate::Event::BondReleased(game_id, 1, 10),
+			crate::Event::BondReleased(game_id, 2, 10),
+			crate::Event::RatingUpdated(1, 1500, 1516),
+			crate::Event::RatingUpdated(2, 1500, 1484),
+			crate::Event::GameFinished(game_id, GameResult::Winner(1)),
+		]);
+
+		run_to_block(6);
+		assert_eq!(rps_events().last(), Some(&crate::Event::GameRemoved(game_id)));
+	});
+}
+
+#[test]
+fn timeout_events_in_order() {
+	new_test_ext().execute_with(|| {
+
+		let salt: [u8; 32] = [1u8;32];
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_game(Origin::signed(1), 2));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+		assert_ok!(RockPaperScissor::choose(Origin::signed(1),
+			RockPaperScissor::commitment(game_id, 0, &1, WeaponType::Rock, salt)));
+
+		// player 2 doesn't choose in time
+		let before = rps_events().len();
+		run_to_block(11);
+		assert_eq!(rps_events()[before..].to_vec(), vec![
+			crate::Event::BondSlashed(game_id, 2, 10),
+			crate::Event::PhaseAdvanced(game_id, Phase::Choose, Phase::Finished),
+			crate::Event::BondReleased(game_id, 1, 10),
+			crate::Event::RatingUpdated(1, 1500, 1516),
+			crate::Event::RatingUpdated(2, 1500, 1484),
+			crate::Event::GameFinished(game_id, GameResult::Forfeit(1)),
+		]);
+	});
+}
+
+#[test]
+fn series_events_restart_phases() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_series(Origin::signed(1), 2, 3));
+		let game_id = RockPaperScissor::player_game(1);
+		play_game(game_id, WeaponType::Rock, WeaponType::Scissor);
+
+		// the next round of the series starts with the initiate phase
+		let events = rps_events();
+		assert_eq!(events[events.len() - 2..].to_vec(), vec![
+			crate::Event::RoundWon(game_id, 0, 1),
+			crate::Event::PhaseAdvanced(game_id, Phase::Reveal, Phase::Initiate),
+		]);
+	});
+}
+
+#[test]
+fn admin_aborts_running_game() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		assert_ok!(RockPaperScissor::new_wagered_game(Origin::signed(1), 2, 100));
+		let game_id = RockPaperScissor::player_game(1);
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(1)));
+		assert_ok!(RockPaperScissor::initiate(Origin::signed(2)));
+
+		assert_noop!(RockPaperScissor::abort_game(Origin::signed(1), game_id), sp_runtime::DispatchError::BadOrigin);
+		assert_noop!(RockPaperScissor::abort_game(Origin::root(), H256::repeat_byte(1)), Error::<Test>::GameDoesntExist);
+
+		// works while paused
+		assert_ok!(RockPaperScissor::set_paused(Origin::root(), true, 0));
+		assert_ok!(RockPaperScissor::abort_game(Origin::root(), game_id));
+		assert_eq!(RockPaperScissor::games(game_id).match_state, MatchState::Finished(GameResult::Abandoned));
+		assert_eq!(RockPaperScissor::phase_deadline(game_id), None);
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Balances::free_balance(2), 1_000);
+		assert!(rps_events().contains(&crate::Event::PhaseAdvanced(game_id, Phase::Choose, Phase::Finished)));
+
+		assert_noop!(RockPaperScissor::abort_game(Origin::root(), game_id), Error::<Test>::NoRunningPhase);
+	});
+}
+
+#[test]
+fn genesis_sets_current_storage_version() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(RockPaperScissor::storage_version(), Releases::V3);
+	});
+}
+
+#[test]
+fn migration_removes_template_storage() {
+	new_test_ext().execute_with(|| {
+
+		// storage of a chain with the template layout
+		StorageVersion::<Test>::put(Releases::V1);
+		frame_support::migration::put_storage_value(b"RockPaperScissor", b"Something", &[], 42u32);
+
+		migrations::RemoveTemplateStorage::<Test>::on_runtime_upgrade();
+
+		assert!(!frame_support::migration::have_storage_value(b"RockPaperScissor", b"Something", &[]));
+		assert_eq!(RockPaperScissor::storage_version(), Releases::V2);
+
+		// migrated chains are left alone
+		frame_support::migration::put_storage_value(b"RockPaperScissor", b"Something", &[], 42u32);
+		migrations::RemoveTemplateStorage::<Test>::on_runtime_upgrade();
+		assert!(frame_support::migration::have_storage_value(b"RockPaperScissor", b"Something", &[]));
+	});
+}
+
+/// Puts a game of the `V2` layout into storage, with the players pointing to it.
+fn put_legacy_game(game: migrations::v3::OldGame<H256, u64, u64>) {
+	use frame_support::{Identity, StorageHasher};
+
+	frame_support::migration::put_storage_value(b"RockPaperScissor", b"Games", &Identity::hash(&game.id.encode()), game.clone());
+	for player in &game.players {
+		PlayerGame::<Test>::insert(player, game.id);
+	}
+}
+
+/// Puts a choice of the `V2` layout into storage.
+fn put_legacy_choice(game_id: H256, player: u64, choice: migrations::v3::OldChoice<H256>) {
+	use frame_support::{Blake2_128Concat, StorageHasher};
+
+	let key = [Blake2_128Concat::hash(&game_id.encode()), Blake2_128Concat::hash(&player.encode())].concat();
+	frame_support::migration::put_storage_value(b"RockPaperScissor", b"PlayerChoice", &key, choice);
+}
+
+#[test]
+fn migration_translates_legacy_games() {
+	use migrations::v3::{OldChoice, OldGame, OldMatchState, OldWeaponType};
+
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+
+		// storage of a chain with the V2 layout
+		StorageVersion::<Test>::put(Releases::V2);
+		let initiating = H256::repeat_byte(1);
+		let revealing = H256::repeat_byte(2);
+		let won = H256::repeat_byte(3);
+		let drawn = H256::repeat_byte(4);
+		put_legacy_game(OldGame { id: initiating, players: vec![1, 2], last_action: 0, match_state: OldMatchState::Initiate(vec![1, 2]) });
+		put_legacy_game(OldGame { id: revealing, players: vec![3, 4], last_action: 0, match_state: OldMatchState::Reveal(vec![4]) });
+		put_legacy_choice(revealing, 3, OldChoice::Reveal(OldWeaponType::Paper));
+		put_legacy_choice(revealing, 4, OldChoice::Choose(H256::repeat_byte(9)));
+		put_legacy_game(OldGame { id: won, players: vec![5, 6], last_action: 0, match_state: OldMatchState::Finished(5) });
+		put_legacy_choice(won, 5, OldChoice::Reveal(OldWeaponType::Rock));
+		put_legacy_choice(won, 6, OldChoice::Reveal(OldWeaponType::Scissor));
+		put_legacy_game(OldGame { id: drawn, players: vec![7, 8], last_action: 0, match_state: OldMatchState::Finished(0) });
+
+		migrations::MigrateToV3::<Test>::on_runtime_upgrade();
+		assert_eq!(RockPaperScissor::storage_version(), Releases::V3);
+
+		// running games keep their players and get a full phase
+		let game = RockPaperScissor::games(initiating);
+		assert_eq!(game.players, bounded(vec![1, 2]));
+		assert_eq!(game.match_state, MatchState::Initiate(bounded(vec![1, 2])));
+		assert_eq!(game.stake, 0);
+		assert_eq!(game.best_of, 1);
+		assert_eq!(game.scores, bounded(vec![(1, 0), (2, 0)]));
+		assert_eq!(game.last_action, 1);
+		assert_eq!(RockPaperScissor::phase_deadline(initiating), Some(1 + <Test as Config>::InitiateTimeout::get()));
+
+		// players of games waiting for reveals commit again
+		assert_eq!(RockPaperScissor::games(revealing).match_state, MatchState::Choose(bounded(vec![3, 4])));
+		assert_eq!(RockPaperScissor::player_choice(revealing, 3), Choice::None);
+		assert_eq!(RockPaperScissor::player_choice(revealing, 4), Choice::None);
+		assert_eq!(RockPaperScissor::phase_deadline(revealing), Some(1 + <Test as Config>::ChooseTimeout::get()));
+
+		// finished games get a result and are removed later
+		assert_eq!(RockPaperScissor::games(won).match_state, MatchState::Finished(GameResult::Winner(5)));
+		assert_eq!(RockPaperScissor::player_choice(won, 5), Choice::Reveal(WeaponType::Rock));
+		assert_eq!(RockPaperScissor::player_choice(won, 6), Choice::Reveal(WeaponType::Scissor));
+		assert_eq!(RockPaperScissor::games(drawn).match_state, MatchState::Finished(GameResult::Draw));
+		assert_eq!(RockPaperScissor::phase_deadline(won), None);
+		assert_eq!(RockPaperScissor::running_games(), 2);
+
+		// migrated chains are left alone
+		let game = RockPaperScissor::games(initiating);
+		migrations::MigrateToV3::<Test>::on_runtime_upgrade();
+		assert_eq!(RockPaperScissor::games(initiating), game);
+
+		// migrated games can be played
+		play_game(initiating, WeaponType::Rock, WeaponType::Scissor);
+		assert_eq!(RockPaperScissor::games(initiating).match_state, MatchState::Finished(GameResult::Winner(1)));
+
+		run_to_block(1 + <Test as Config>::CleanupDelay::get());
+		assert!(!Games::<Test>::contains_key(won));
+		assert!(!Games::<Test>::contains_key(drawn));
+		assert!(!PlayerGame::<Test>::contains_key(5));
+		assert!(!PlayerGame::<Test>::contains_key(8));
+		assert_eq!(RockPaperScissor::player_choice(won, 5), Choice::None);
+	});
+}
+
+#[test]
+fn migration_keeps_weapon_of_legacy_choices() {
+	use migrations::v3::{OldChoice, OldWeaponType, translate_choice};
+
+	assert_eq!(translate_choice::<H256, WeaponType>(OldChoice::Reveal(OldWeaponType::Paper)), Some(Choice::Reveal(WeaponType::Paper)));
+	assert_eq!(translate_choice::<H256, WeaponType>(OldChoice::Choose(H256::repeat_byte(1))), None);
+	assert_eq!(translate_choice::<H256, WeaponType>(OldChoice::None), Some(Choice::None));
+}
+
+#[test]
+fn matchmaker_creates_max_games_per_block() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+
+		let max_games = <Test as Config>::MaxGamesPerBlock::get() as u64;
+		for player in 1..=2 * (max_games + 1) {
+			assert_ok!(RockPaperScissor::queue(Origin::signed(player)));
+		}
+
+		run_next_block();
+		let playing = (1..=2 * (max_games + 1)).filter(|player| PlayerGame::<Test>::contains_key(player)).count();
+		assert_eq!(playing as u64, 2 * max_games);
+
+		// the last match is created a block later
+		run_next_block();
+		assert!((1..=2 * (max_games + 1)).all(|player| PlayerGame::<Test>::contains_key(player)));
+	});
+}
+
+#[test]
+fn cleanup_of_full_block_moves_to_next_block() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+
+		let max_games = <Test as Config>::MaxGamesPerBlock::get() as u64;
+		let games: Vec<H256> = (0..=max_games)
+			.map(|index| RockPaperScissor::create_game(bounded(vec![10 + 2 * index, 11 + 2 * index]), 1, 0))
+			.collect();
+		for game_id in &games {
+			assert_ok!(RockPaperScissor::abort_game(Origin::root(), *game_id));
+		}
+
+		let cleanup = 1 + <Test as Config>::CleanupDelay::get();
+		assert_eq!(RockPaperScissor::game_cleanup(cleanup).len() as u64, max_games);
+		assert_eq!(RockPaperScissor::game_cleanup(cleanup + 1).into_inner(), vec![games[max_games as usize]]);
+
+		run_to_block(cleanup);
+		assert!(games[..max_games as usize].iter().all(|game_id| !Games::<Test>::contains_key(game_id)));
+		assert!(Games::<Test>::contains_key(games[max_games as usize]));
+
+		run_next_block();
+		assert!(!Games::<Test>::contains_key(games[max_games as usize]));
+	});
+}
+
+#[test]
+fn stored_game_fits_max_encoded_len() {
+	new_test_ext().execute_with(|| {
+		let players = bounded(vec![1, 2, 3, 4]);
+		let game_id = RockPaperScissor::create_game(players.clone(), 3, 100);
+
+		// every bounded vec at its bound
+		let mut game = RockPaperScissor::games(game_id);
+		game.staked = players.clone();
+		game.bonded = players.clone();
+		game.match_state = MatchState::Reveal(players);
+		assert_eq!(game.players.len() as u32, <Test as Config>::MaxPlayers::get());
+		assert!(game.encode().len() <= GameOf::<Test>::max_encoded_len());
+
+		assert_eq!(
+			MatchStateOf::<Test>::max_encoded_len(),
+			1 + BoundedVec::<u64, MaxPlayers>::max_encoded_len()
+		);
+		assert_eq!(Choice::<H256, WeaponType>::max_encoded_len(), 1 + 32);
+	});
+}